@@ -0,0 +1,26 @@
+alias Csp.Problems
+
+# Run with `mix run benches/solver_modes.exs`.
+Benchee.run(
+  %{
+    backtracking: fn csp -> Csp.solve(csp, method: :backtracking) end,
+    forward_checking: fn csp -> Csp.solve(csp, method: :backtracking, forward_checking: true) end,
+    forward_checking_minimum_remaining_values: fn csp ->
+      Csp.solve(csp, method: :backtracking, forward_checking: true, variable_selector: :minimum_remaining_values)
+    end,
+    maintaining_arc_consistency: fn csp -> Csp.solve(csp, method: :backtracking, ac3: true) end,
+    backjumping: fn csp -> Csp.solve(csp, method: :backjumping) end
+  },
+  inputs: %{
+    "10 Queens" => Problems.nqueens(10),
+    "12 Queens" => Problems.nqueens(12),
+    # close to the satisfiability phase transition for these parameters
+    "Random binary CSP (20 variables, 10 values, density 0.5, tightness 0.38)" =>
+      Problems.random_binary(20, 10, 0.5, 0.38, 42),
+    "Wikipedia Sudoku" => Problems.wiki_sudoku()
+  },
+  formatters: [
+    Benchee.Formatters.Console,
+    {Benchee.Formatters.CSV, file: "solver_modes_benchmark.csv"}
+  ]
+)
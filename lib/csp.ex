@@ -8,7 +8,7 @@ defmodule Csp do
 
   Additionally, you can test some example problems with the provided escript CLI.
   """
-  alias Csp.{Constraint, AC3, Backtracking, MinConflicts, Searcher}
+  alias Csp.{Constraint, AC3, Backjumping, Backtracking, MinConflicts, Restarts, Searcher}
 
   @type variable :: atom
   @type value :: any
@@ -16,16 +16,29 @@ defmodule Csp do
   @type constraint :: (value -> boolean) | (value, value -> boolean)
   @type assignment :: %{variable => value}
 
-  @type solve_result :: {:solved, assignment() | [assignment()]} | :no_solution
+  @type solve_result :: {:solved, assignment() | [assignment()]} | :no_solution | :timeout | :restarts_exhausted
   @type solver_status :: :solved | :reduced | :no_solution
+  @type validation_error ::
+          {:duplicate_variable, variable()}
+          | {:missing_domain, variable()}
+          | {:empty_domain, variable()}
+          | {:unknown_variable, variable(), constraint_index :: non_neg_integer()}
 
+  @type problem_stats :: %{
+          variables: non_neg_integer(),
+          domain_sizes: non_neg_integer(),
+          constraints: non_neg_integer(),
+          arities: %{(arity :: non_neg_integer()) => constraints_count :: non_neg_integer()},
+          density: float(),
+          tightness: [float()]
+        }
   @type t :: %__MODULE__{
           variables: [atom],
           domains: %{variable => domain},
           constraints: [Constraint.t()]
         }
 
-  defstruct [:variables, :domains, :constraints]
+  defstruct variables: [], domains: %{}, constraints: []
 
   @doc """
   Solves a CSP.
@@ -37,32 +50,192 @@ defmodule Csp do
   - `method`, can be one of the following:
     - `:backtracking` - backtracking search, selected by default
     - `:min_conflicts` - min-conflicts algorithm with tabu search
+    - `:restarts` - randomized backtracking with restarts; returns `:restarts_exhausted` if it gives up
+    - `:backjumping` - conflict-directed backjumping
     - `:ac3` - AC-3 algorithm followed by backtracking
     - `:brute_force` - brute-force search.
+  - `timeout`, non-negative integer (milliseconds) or `nil` (default). If set, the solver is run in a separate process,
+  which is killed if it doesn't finish in time; `:timeout` is returned in that case.
 
   You can pass options to backtracking (see `Csp.Backtracking.solve/2` docs),
-  min-conflicts (`Csp.MinConflicts.solve/2`), or brute-force (see `Csp.Searcher.brute_force/2`)
-  in this function's `opts`.
+  min-conflicts (`Csp.MinConflicts.solve/2`), restarts (`Csp.Restarts.solve/2`),
+  or brute-force (see `Csp.Searcher.brute_force/2`) in this function's `opts`.
   """
   @spec solve(t(), Keyword.t()) :: solve_result()
   def solve(%__MODULE__{} = csp, opts \\ []) do
-    method = Keyword.get(opts, :method, :backtracking)
+    case Keyword.get(opts, :timeout) do
+      nil ->
+        solve_with_method(csp, opts)
 
-    case method do
-      :backtracking ->
-        Backtracking.solve(csp, opts)
+      timeout ->
+        task = Task.async(fn -> solve_with_method(csp, opts) end)
 
-      :min_conflicts ->
-        MinConflicts.solve(csp, opts)
+        case Task.yield(task, timeout) || Task.shutdown(task, :brutal_kill) do
+          {:ok, result} -> result
+          nil -> :timeout
+        end
+    end
+  end
 
-      :brute_force ->
-        Searcher.brute_force(csp, opts)
+  @doc """
+  Solves each of the `csps` concurrently, returning the list of results in the same order.
 
-      :ac3 ->
-        case AC3.solve(csp) do
-          {status, csp} when status in [:solved, :reduced] -> Backtracking.solve(csp, opts)
-          {:no_solution, _} -> :no_solution
+  Each CSP is validated with `validate/1` first, and an invalid one gets `{:error, reason}` as its result
+  instead of failing the whole batch. `opts` are passed to `solve/2` for every CSP; the `timeout` option
+  applies to each CSP separately. CSPs are solved in separate processes, up to `System.schedulers_online/0`
+  at a time.
+  """
+  @spec solve_many([t()], Keyword.t()) :: [solve_result() | {:error, validation_error()}]
+  def solve_many(csps, opts \\ []) when is_list(csps) do
+    csps
+    |> Task.async_stream(
+      fn csp ->
+        case validate(csp) do
+          :ok -> solve(csp, opts)
+          {:error, _reason} = error -> error
         end
+      end,
+      timeout: :infinity
+    )
+    |> Enum.map(fn {:ok, result} -> result end)
+  end
+
+  @doc """
+  Solves `csp`, extending a known `partial_assignment` to the remaining variables.
+
+  Domains of variables in `partial_assignment` are narrowed to their assigned values, so only the rest
+  of the variables are searched. If some assigned value is not in its variable's domain,
+  or `partial_assignment` violates a constraint of `csp`, returns `:no_solution` without searching.
+  `opts` are passed to `solve/2`.
+  """
+  @spec solve_from(t(), assignment(), Keyword.t()) :: solve_result()
+  def solve_from(%__MODULE__{} = csp, partial_assignment, opts \\ []) do
+    in_domains? = Enum.all?(partial_assignment, fn {variable, value} -> value in Map.get(csp.domains, variable, []) end)
+
+    if in_domains? and consistent?(csp, partial_assignment) do
+      pinned_domains = Map.new(partial_assignment, fn {variable, value} -> {variable, [value]} end)
+      solve(%{csp | domains: Map.merge(csp.domains, pinned_domains)}, opts)
+    else
+      :no_solution
+    end
+  end
+
+  @doc """
+  Creates a new CSP from a list of `variables`, a map of their `domains`, and a list of `constraints`,
+  validating it with `validate/1`.
+
+  Returns `{:ok, csp}` or `{:error, reason}`.
+  This is the recommended way to create a CSP when you already have all of its parts.
+  """
+  @spec new([variable()], %{variable() => domain()}, [Constraint.t()]) :: {:ok, t()} | {:error, validation_error()}
+  def new(variables, domains, constraints) do
+    build(%__MODULE__{variables: variables, domains: domains, constraints: constraints})
+  end
+
+  @doc """
+  Adds `variable` with `domain` to `csp`.
+
+  Together with `add_constraint/2` and `build/1` allows constructing a CSP incrementally:
+
+  ```elixir
+  %Csp{}
+  |> Csp.add_variable(:x, [1, 2, 3])
+  |> Csp.add_variable(:y, [1, 2, 3])
+  |> Csp.add_constraint({[:x, :y], fn [x, y] -> x != y end})
+  |> Csp.build()
+  ```
+  """
+  @spec add_variable(t(), variable(), domain()) :: t()
+  def add_variable(%__MODULE__{} = csp, variable, domain) do
+    %{csp | variables: csp.variables ++ [variable], domains: Map.put(csp.domains, variable, domain)}
+  end
+
+  @doc """
+  Adds `constraint` to `csp`.
+  """
+  @spec add_constraint(t(), Constraint.t()) :: t()
+  def add_constraint(%__MODULE__{} = csp, constraint) do
+    %{csp | constraints: csp.constraints ++ [constraint]}
+  end
+
+  @doc """
+  Finishes building `csp`, validating it with `validate/1`.
+
+  Returns `{:ok, csp}` or `{:error, reason}`.
+  """
+  @spec build(t()) :: {:ok, t()} | {:error, validation_error()}
+  def build(%__MODULE__{} = csp) do
+    case validate(csp) do
+      :ok -> {:ok, csp}
+      {:error, _reason} = error -> error
+    end
+  end
+
+  @doc """
+  Validates `csp`, checking that variables are unique, every variable has a non-empty domain,
+  and every constraint references only variables of `csp`.
+
+  Returns `:ok`, or `{:error, reason}`, where `reason` is one of:
+
+  - `{:duplicate_variable, variable}` - `variable` is listed more than once in `csp.variables`
+  - `{:missing_domain, variable}` - `variable` has no entry in `csp.domains`
+  - `{:empty_domain, variable}` - `variable`'s domain is empty, so `csp` can have no solutions
+  - `{:unknown_variable, variable, constraint_index}` - constraint at `constraint_index`
+  in `csp.constraints` references `variable`, which is not in `csp.variables`
+  """
+  @spec validate(t()) :: :ok | {:error, validation_error()}
+  def validate(%__MODULE__{} = csp) do
+    duplicate_variable = List.first(csp.variables -- Enum.uniq(csp.variables))
+    missing_domain = Enum.find(csp.variables, fn variable -> !Map.has_key?(csp.domains, variable) end)
+    empty_domain = Enum.find(csp.variables, fn variable -> Map.get(csp.domains, variable) == [] end)
+
+    unknown_variable =
+      csp.constraints
+      |> Enum.with_index()
+      |> Enum.find_value(fn {constraint, constraint_index} ->
+        case Enum.find(Constraint.arguments(constraint), &(&1 not in csp.variables)) do
+          nil -> nil
+          variable -> {:unknown_variable, variable, constraint_index}
+        end
+      end)
+
+    cond do
+      !is_nil(duplicate_variable) -> {:error, {:duplicate_variable, duplicate_variable}}
+      !is_nil(missing_domain) -> {:error, {:missing_domain, missing_domain}}
+      !is_nil(empty_domain) -> {:error, {:empty_domain, empty_domain}}
+      !is_nil(unknown_variable) -> {:error, unknown_variable}
+      true -> :ok
+    end
+  end
+
+  @doc """
+  Explains why `csp` has no solution.
+
+  If `csp` is solvable, returns the result of `solve/2`. Otherwise, returns `{:no_solution, conflict_set}`,
+  where `conflict_set` is a minimal list of constraints from `csp` that cannot be satisfied together:
+  removing any of them from `conflict_set` makes it satisfiable.
+
+  The conflict set is found by trying to drop constraints one by one, so this runs the solver
+  once per constraint; `opts` are passed to `solve/2`.
+  """
+  @spec conflict_set(t(), Keyword.t()) :: solve_result() | {:no_solution, [Constraint.t()]}
+  def conflict_set(%__MODULE__{} = csp, opts \\ []) do
+    case solve(csp, opts) do
+      :no_solution ->
+        conflict_set =
+          Enum.reduce(csp.constraints, csp.constraints, fn constraint, conflict_set ->
+            reduced_conflict_set = List.delete(conflict_set, constraint)
+
+            case solve(%{csp | constraints: reduced_conflict_set}, opts) do
+              :no_solution -> reduced_conflict_set
+              _ -> conflict_set
+            end
+          end)
+
+        {:no_solution, conflict_set}
+
+      result ->
+        result
     end
   end
 
@@ -85,6 +258,83 @@ defmodule Csp do
     end)
   end
 
+  @doc """
+  Returns a map from each constrained variable of `csp` to the list of constraints
+  that have it as one of their arguments (in the order of `csp.constraints`).
+
+  Computing it once allows finding constraints affected by an assignment of some variable
+  without scanning all of `csp.constraints`.
+  """
+  @spec constraints_index(t()) :: %{variable() => [Constraint.t()]}
+  def constraints_index(csp) do
+    csp.constraints
+    |> Enum.reverse()
+    |> Enum.reduce(%{}, fn constraint, index ->
+      Constraint.arguments(constraint)
+      |> Enum.uniq()
+      |> Enum.reduce(index, fn variable, index -> Map.update(index, variable, [constraint], &[constraint | &1]) end)
+    end)
+  end
+
+  @doc """
+  Returns a set of variables sharing at least one constraint with `variable` in `csp`.
+  """
+  @spec neighbours(t(), variable()) :: MapSet.t(variable())
+  def neighbours(csp, variable) do
+    constraints_on(csp, variable)
+    |> Enum.flat_map(&Constraint.arguments/1)
+    |> MapSet.new()
+    |> MapSet.delete(variable)
+  end
+
+  @doc """
+  Returns statistics describing the structure of `csp`, without solving it:
+
+  - `variables` - the number of variables
+  - `domain_sizes` - the total number of values in all variables' domains
+  - `constraints` - the number of constraints
+  - `arities` - a map from constraint arity (the number of distinct arguments) to the number of constraints with it
+  - `density` - the fraction of pairs of variables that share at least one constraint (`0.0` for less than 2 variables)
+  - `tightness` - for each binary constraint, in the order of `csp.constraints`,
+  the fraction of pairs of values from its arguments' domains that violate it
+  """
+  @spec problem_stats(t()) :: problem_stats()
+  def problem_stats(%__MODULE__{} = csp) do
+    arguments = Enum.map(csp.constraints, &Enum.uniq(Constraint.arguments(&1)))
+    n = length(csp.variables)
+
+    connected_pairs =
+      arguments
+      |> Enum.flat_map(fn arguments -> for x <- arguments, y <- arguments, x < y, do: {x, y} end)
+      |> Enum.uniq()
+      |> length()
+
+    tightness =
+      Enum.zip(csp.constraints, arguments)
+      |> Enum.flat_map(fn
+        {constraint, [x, y]} ->
+          pairs =
+            for x_value <- Map.fetch!(csp.domains, x), y_value <- Map.fetch!(csp.domains, y) do
+              %{x => x_value, y => y_value}
+            end
+
+          violating = Enum.count(pairs, &(!Constraint.satisfies?(constraint, &1)))
+          [if(pairs == [], do: 0.0, else: violating / length(pairs))]
+
+        _ ->
+          []
+      end)
+
+    %{
+      variables: n,
+      domain_sizes: csp.domains |> Map.take(csp.variables) |> Map.values() |> Enum.map(&length/1) |> Enum.sum(),
+      constraints: length(csp.constraints),
+      arities: Enum.frequencies_by(arguments, &length/1),
+      density: if(n < 2, do: 0.0, else: connected_pairs / (n * (n - 1) / 2)),
+      tightness: tightness
+    }
+  end
+
   @doc """
   Checks if (possibly partial) `assignment` satisfies all constraints in `csp`,
   for which it has enough assigned variables.
@@ -154,4 +404,68 @@ defmodule Csp do
       count_conflicts(csp, assignment)
     end)
   end
+
+  @doc """
+  Formats `assignment` as a human-readable string, with one `variable = value` line per variable,
+  sorted by variable.
+  """
+  @spec format_assignment(assignment()) :: String.t()
+  def format_assignment(assignment) do
+    assignment
+    |> Enum.sort()
+    |> Enum.map(fn {variable, value} -> "#{inspect(variable)} = #{inspect(value)}" end)
+    |> Enum.join("\n")
+  end
+
+  ## Helpers
+
+  @spec solve_with_method(t(), Keyword.t()) :: solve_result()
+  defp solve_with_method(csp, opts) do
+    method = Keyword.get(opts, :method, :backtracking)
+
+    case method do
+      :backtracking ->
+        Backtracking.solve(csp, opts)
+
+      :min_conflicts ->
+        MinConflicts.solve(csp, opts)
+
+      :restarts ->
+        Restarts.solve(csp, opts)
+
+      :backjumping ->
+        Backjumping.solve(csp, opts)
+
+      :brute_force ->
+        Searcher.brute_force(csp, opts)
+
+      :ac3 ->
+        case AC3.solve(csp) do
+          {status, csp} when status in [:solved, :reduced] -> Backtracking.solve(csp, opts)
+          {:no_solution, _} -> :no_solution
+        end
+    end
+  end
+end
+
+defimpl String.Chars, for: Csp do
+  alias Csp.Constraint
+
+  def to_string(csp) do
+    variables =
+      Enum.map(csp.variables, fn variable ->
+        "  #{inspect(variable)}: #{length(Map.get(csp.domains, variable, []))} values"
+      end)
+
+    constraints =
+      Enum.map(csp.constraints, fn constraint ->
+        "  on #{Constraint.arguments(constraint) |> Enum.map(&inspect/1) |> Enum.join(", ")}"
+      end)
+
+    Enum.join(
+      ["CSP with #{length(csp.variables)} variables and #{length(csp.constraints)} constraints", "Variables:"] ++
+        variables ++ ["Constraints:"] ++ constraints,
+      "\n"
+    )
+  end
 end
@@ -8,7 +8,8 @@ defmodule Csp.AC3 do
 
   @type unassigned :: [Csp.variable()]
   @type domain_reduction :: {Csp.t(), Csp.assignment(), unassigned()}
-  @type reduce_result :: {:ok, Csp.t(), Csp.assignment(), unassigned()} | :no_solution
+  @type wipeout :: {Csp.variable(), Constraint.t()}
+  @type reduce_result :: {:ok, Csp.t(), Csp.assignment(), unassigned()} | {:no_solution, wipeout()}
 
   @doc """
   Tries to solve `csp` with AC-3 algorithm, applying node and arc consistency.
@@ -42,7 +43,9 @@ defmodule Csp.AC3 do
   simplified version of domain reduction for constraint: it doesn't attempt
   to track affected constraints when reducing some constraint's domain.
 
-  Returns `:no_solution` if an inconsistency is detected, or a tuple of
+  Returns `{:no_solution, {variable, constraint}}` if an inconsistency is detected,
+  where `variable` is the variable whose domain became empty, and `constraint` is the constraint
+  that removed the last values from it. Otherwise, returns a tuple of
   `{:ok, csp, assignment, unassigned}`, where domains of `csp` are reduced,
   `assignment` is amended with inferred variable assignments, and
   `unassigned` list is updated to reflect those assignment changes.
@@ -52,6 +55,65 @@ defmodule Csp.AC3 do
     reduce(csp, assignment, unassigned, csp.constraints)
   end
 
+  @doc """
+  Enforces node consistency: removes values violating unary constraints from the domains of `csp`.
+
+  This is a cheap preprocessing step, applying each unary constraint once; `solve/1` also does it
+  as part of its run. Returns `{:ok, csp}` with reduced domains, or `:no_solution` if some domain becomes empty.
+  """
+  @spec node_consistency(Csp.t()) :: {:ok, Csp.t()} | :no_solution
+  def node_consistency(csp) do
+    csp.constraints
+    |> Enum.reduce_while({:ok, csp}, fn constraint, {:ok, csp} ->
+      case Constraint.arguments(constraint) do
+        [variable] ->
+          reduced_domain =
+            Map.fetch!(csp.domains, variable)
+            |> Enum.filter(fn value -> Constraint.satisfies?(constraint, %{variable => value}) end)
+
+          case reduced_domain do
+            [] -> {:halt, :no_solution}
+            _ -> {:cont, {:ok, %{csp | domains: Map.put(csp.domains, variable, reduced_domain)}}}
+          end
+
+        _ ->
+          {:cont, {:ok, csp}}
+      end
+    end)
+  end
+
+  @doc """
+  Returns a map from each value in `variable`'s domain to the number of its supports in `csp`:
+  values of neighbouring variables that are compatible with it, summed over all binary constraints
+  on `variable`.
+
+  A value with `0` supports for some constraint would be removed by arc consistency;
+  this is mostly useful to inspect how propagation sees the current domains.
+  """
+  @spec support_counts(Csp.t(), Csp.variable()) :: %{Csp.value() => non_neg_integer()}
+  def support_counts(csp, variable) do
+    binary_constraints =
+      Csp.constraints_on(csp, variable)
+      |> Enum.flat_map(fn constraint ->
+        case Constraint.arguments(constraint) do
+          [^variable, neighbour] -> [{constraint, neighbour}]
+          [neighbour, ^variable] -> [{constraint, neighbour}]
+          _ -> []
+        end
+      end)
+
+    Map.fetch!(csp.domains, variable)
+    |> Map.new(fn value ->
+      supports =
+        Enum.map(binary_constraints, fn {constraint, neighbour} ->
+          Map.fetch!(csp.domains, neighbour)
+          |> Enum.count(&Constraint.satisfies?(constraint, %{variable => value, neighbour => &1}))
+        end)
+
+      {value, Enum.sum(supports)}
+    end)
+  end
+
   ## Helpers
 
   @spec solve(Csp.t(), [constraint :: any()]) :: Csp.t()
@@ -156,7 +218,7 @@ defmodule Csp.AC3 do
 
         case reduced_domain do
           [] ->
-            :no_solution
+            {:no_solution, {variable, constraint}}
 
           _ ->
             {csp, assignment, unassigned} =
@@ -167,13 +229,12 @@ defmodule Csp.AC3 do
 
       # arc consistency for binary constraints
       [x, y] ->
-        {csp, assignment, unassigned} =
-          enforce_arc_consistency_and_assign(csp, constraint, assignment, unassigned, x, y)
-
-        {csp, assignment, unassigned} =
-          enforce_arc_consistency_and_assign(csp, constraint, assignment, unassigned, y, x)
-
-        reduce(csp, assignment, unassigned, remaining_constraints)
+        with {:ok, csp, assignment, unassigned} <-
+               enforce_arc_consistency_and_assign(csp, constraint, assignment, unassigned, x, y),
+             {:ok, csp, assignment, unassigned} <-
+               enforce_arc_consistency_and_assign(csp, constraint, assignment, unassigned, y, x) do
+          reduce(csp, assignment, unassigned, remaining_constraints)
+        end
 
       # skip higher arity constraints
       k_ary when is_list(k_ary) ->
@@ -215,7 +276,7 @@ defmodule Csp.AC3 do
           unassigned(),
           Csp.variable(),
           Csp.variable()
-        ) :: domain_reduction()
+        ) :: reduce_result()
   defp enforce_arc_consistency_and_assign(csp, constraint, assignment, unassigned, x, y) do
     x_original_domain = Map.fetch!(csp.domains, x)
     y_original_domain = Map.fetch!(csp.domains, y)
@@ -227,6 +288,13 @@ defmodule Csp.AC3 do
         end)
       end)
 
-    reduce_domain_and_assign(csp, x, assignment, unassigned, x_reduced_domain)
+    case x_reduced_domain do
+      [] ->
+        {:no_solution, {x, constraint}}
+
+      _ ->
+        {csp, assignment, unassigned} = reduce_domain_and_assign(csp, x, assignment, unassigned, x_reduced_domain)
+        {:ok, csp, assignment, unassigned}
+    end
   end
 end
@@ -0,0 +1,135 @@
+defmodule Csp.Backjumping do
+  @moduledoc """
+  [Conflict-directed backjumping](https://en.wikipedia.org/wiki/Backjumping) implementation.
+
+  Compared to chronological backtracking, when all values of a variable fail,
+  the search jumps back directly to the most recently assigned variable responsible for the failure,
+  skipping the variables that have nothing to do with it. Since only irrelevant parts of the search space
+  are skipped, it finds the same first solution as `Csp.Backtracking.solve/2` with the default options.
+  """
+  alias Csp.Constraint
+
+  @type stats :: %{nodes: non_neg_integer(), backjumps: non_neg_integer(), elapsed: non_neg_integer()}
+
+  @typep conflict_set :: MapSet.t(Csp.variable())
+  @typep backjump_result :: {:solved, Csp.assignment()} | {:conflict, conflict_set()}
+  @typep constraints_index :: %{Csp.variable() => [Constraint.t()]}
+
+  # options of `Csp.Backtracking.solve/2` changing the search, which backjumping doesn't support
+  @unsupported_options [:all, :ac3, :forward_checking, :variable_selector, :value_selector, :parallel, :prune]
+
+  # indexes of the `:counters` used for collecting stats
+  @stats_counters %{nodes: 1, backjumps: 2}
+
+  @doc """
+  Solves `csp` with conflict-directed backjumping, assigning variables in the order of `csp.variables`,
+  and trying values in the order of their domains.
+
+  Only the first solution is searched for, and there is no inference, so passing any of
+  the search options of `Csp.Backtracking.solve/2` (`all`, `ac3`, `forward_checking`, `variable_selector`,
+  `value_selector`, `parallel`, or `prune`) raises `ArgumentError`. Other `opts` are ignored.
+
+  Returns `{:solved, assignment}` or `:no_solution`.
+  """
+  @spec solve(Csp.t(), Keyword.t()) :: {:solved, Csp.assignment()} | :no_solution
+  def solve(%Csp{} = csp, opts \\ []) do
+    validate_options!(opts)
+    run(csp, nil)
+  end
+
+  @doc """
+  Same as `solve/2`, but also returns search statistics: `{result, stats}`.
+
+  `stats` is a map with the following keys:
+
+  - `nodes` - the number of values tried for variables, counted the same way as by
+  `Csp.Backtracking.solve_with_stats/2`, so the two can be compared
+  - `backjumps` - the number of variables jumped over, since they weren't responsible for a conflict
+  - `elapsed` - the search duration, in microseconds
+  """
+  @spec solve_with_stats(Csp.t(), Keyword.t()) :: {{:solved, Csp.assignment()} | :no_solution, stats()}
+  def solve_with_stats(%Csp{} = csp, opts \\ []) do
+    validate_options!(opts)
+    counters = :counters.new(map_size(@stats_counters), [:atomics])
+    {elapsed, result} = :timer.tc(fn -> run(csp, counters) end)
+
+    stats =
+      @stats_counters
+      |> Enum.map(fn {name, index} -> {name, :counters.get(counters, index)} end)
+      |> Enum.into(%{elapsed: elapsed})
+
+    {result, stats}
+  end
+
+  ## Helpers
+
+  @spec validate_options!(Keyword.t()) :: :ok
+  defp validate_options!(opts) do
+    case Keyword.take(opts, @unsupported_options) do
+      [] -> :ok
+      unsupported -> raise ArgumentError, "unsupported backjumping options: #{inspect(Keyword.keys(unsupported))}"
+    end
+  end
+
+  @spec run(Csp.t(), :counters.counters_ref() | nil) :: {:solved, Csp.assignment()} | :no_solution
+  defp run(csp, counters) do
+    case backjump(%{}, csp.variables, csp, Csp.constraints_index(csp), counters) do
+      {:solved, _assignment} = solved -> solved
+      {:conflict, _conflict_set} -> :no_solution
+    end
+  end
+
+  @spec increment(:counters.counters_ref() | nil, :nodes | :backjumps) :: :ok
+  defp increment(nil, _counter), do: :ok
+  defp increment(counters, counter), do: :counters.add(counters, @stats_counters[counter], 1)
+
+  @spec backjump(Csp.assignment(), [Csp.variable()], Csp.t(), constraints_index(), :counters.counters_ref() | nil) ::
+          backjump_result()
+  defp backjump(assignment, unassigned, csp, constraints_index, counters)
+
+  defp backjump(assignment, [], _csp, _constraints_index, _counters), do: {:solved, assignment}
+
+  defp backjump(assignment, [variable | unassigned], csp, constraints_index, counters) do
+    constraints = Map.get(constraints_index, variable, [])
+
+    Map.fetch!(csp.domains, variable)
+    |> Enum.reduce_while({:conflict, MapSet.new()}, fn value, {:conflict, conflict_set} ->
+      assignment = Map.put(assignment, variable, value)
+      increment(counters, :nodes)
+
+      case violated_constraints_arguments(constraints, assignment) do
+        [] ->
+          case backjump(assignment, unassigned, csp, constraints_index, counters) do
+            {:solved, _assignment} = solved ->
+              {:halt, solved}
+
+            {:conflict, future_conflict_set} ->
+              if variable in future_conflict_set do
+                {:cont, {:conflict, MapSet.union(conflict_set, MapSet.delete(future_conflict_set, variable))}}
+              else
+                # `variable` is not responsible for the conflict; jump back over it
+                increment(counters, :backjumps)
+                {:halt, {:conflict, future_conflict_set}}
+              end
+          end
+
+        arguments ->
+          {:cont, {:conflict, MapSet.union(conflict_set, MapSet.new(arguments) |> MapSet.delete(variable))}}
+      end
+    end)
+  end
+
+  # Returns the arguments of all fully assigned constraints from `constraints` that `assignment` violates.
+  @spec violated_constraints_arguments([Constraint.t()], Csp.assignment()) :: [Csp.variable()]
+  defp violated_constraints_arguments(constraints, assignment) do
+    Enum.flat_map(constraints, fn constraint ->
+      arguments = Constraint.arguments(constraint)
+
+      if Enum.all?(arguments, &Map.has_key?(assignment, &1)) and !Constraint.satisfies?(constraint, assignment) do
+        arguments
+      else
+        []
+      end
+    end)
+  end
+end
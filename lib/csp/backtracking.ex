@@ -3,9 +3,51 @@ defmodule Csp.Backtracking do
   Backtracking algorithm implementation.
   """
   alias Csp
-  alias Csp.AC3
+  alias Csp.{AC3, Constraint, ConstraintCost}
+  alias Csp.Constraints.{AllDifferent, Cardinality, Linear}
 
-  @type variable_selector :: :take_head | ([Csp.variable()] -> {Csp.variable(), [Csp.variable()]})
+  @type variable_selector ::
+          :take_head
+          | :minimum_remaining_values
+          | :degree
+          | ([Csp.variable()] -> {Csp.variable(), [Csp.variable()]})
+          | ([Csp.variable()], Csp.t() -> {Csp.variable(), [Csp.variable()]})
+  @type value_selector :: :in_order | :least_constraining_value | (Csp.variable(), Csp.domain() -> Csp.domain())
+
+  @typep settings :: %{
+           variable_selector: variable_selector(),
+           value_selector: value_selector(),
+           ac3: boolean(),
+           forward_checking: boolean(),
+           all: boolean(),
+           parallel: boolean(),
+           prune: (Csp.assignment() -> boolean()) | nil,
+           constraints_index: %{Csp.variable() => [{Constraint.t(), counter :: pos_integer()}]},
+           stats: :counters.counters_ref() | nil
+         }
+
+  @type stats :: %{
+          nodes: non_neg_integer(),
+          backtracks: non_neg_integer(),
+          constraint_checks: non_neg_integer(),
+          max_depth: non_neg_integer(),
+          checks_per_constraint: [non_neg_integer()],
+          elapsed: non_neg_integer()
+        }
+
+  # indexes of the `:counters` used for collecting stats;
+  # they are followed by the counters of checks of each constraint, see `constraints_index/1`
+  @stats_counters %{nodes: 1, backtracks: 2, constraint_checks: 3, max_depth: 4}
+
+  # global constraints with a `prune/2` function, used by forward checking and AC-3
+  @pruned_constraints [AllDifferent, Cardinality, Linear]
+
+  # `stream/2` keeps an explicit stack of frames instead of recursing;
+  # each frame holds the values of the selected variable that are yet to be tried.
+  @typep frame :: {Csp.assignment(), Csp.variable(), [Csp.value()], [Csp.variable()], Csp.t()}
+
+  # the result of inference after an assignment, extending the assignment, like `Csp.AC3.reduce/3`
+  @typep inference :: {:ok, Csp.t(), Csp.assignment(), [Csp.variable()]} | {:no_solution, AC3.wipeout() | nil}
 
   @doc """
   Backtracking implementation for solving CSPs.
@@ -15,91 +57,634 @@ defmodule Csp.Backtracking do
   The following `opts` are supported:
 
   - `all`, boolean, `false` by default: if only first, or all variables should be returned.
+  The search is deterministic, so solutions are always returned in the same order: with the default
+  `variable_selector` and `value_selector`, it is the lexicographic order of solutions, comparing values
+  of variables in the order of `csp.variables` by their positions in the variables' domains.
   - `ac3`, boolean, `false` by default: if AC3 runs should be performed during each backtracking step.
+  - `forward_checking`, boolean, `false` by default: if after each assignment values incompatible
+  with it should be removed from the domains of unassigned variables sharing a binary constraint
+  with the assigned variable. If any of those domains becomes empty, the assignment is rejected immediately.
+  Domains of variables sharing a `Csp.Constraints.AllDifferent`, `Csp.Constraints.Cardinality`,
+  or `Csp.Constraints.Linear` constraint with the assigned variable are pruned with the constraint's `prune/2`
+  (this is also done when `ac3` is `true`).
   - `variable_selector`, either `:take_head` (default), `:minimum_remaining_values`
-  (will select the variable with the least values remaining in the domain as the next candidate to consider),
+  (will select the variable with the least values remaining in the domain as the next candidate to consider;
+  ties are broken with the degree heuristic, and then by the order of variables in `csp.variables`),
+  `:degree` (will select the variable participating in the most constraints with other unassigned variables),
   or a function accepting a list of unassigned variables, and returning a tuple
   of a variable we should consider next and a rest of the unassigned variables list.
+  The function can also accept the CSP with the current (possibly pruned) domains as the second argument.
+  - `value_selector`, either `:in_order` (default), trying values in the order they are listed in the domain,
+  or `:least_constraining_value`, trying first the values that rule out the fewest values
+  in the domains of unassigned variables sharing a binary constraint with the selected variable,
+  or a function accepting the selected variable and its current domain, and returning the values in the order
+  they should be tried.
+  - `prune`, a function accepting a partial assignment and returning `true` if the search shouldn't extend it,
+  or `nil` (default). It's called after each consistent assignment, and allows pruning branches with
+  external knowledge, e.g., a budget computed from the assigned variables.
+  - `parallel`, boolean, `false` by default: if the subtrees for each value of the first selected variable
+  should be searched concurrently, in separate processes. When `all` is `false`, the first found solution
+  is returned (which is not necessarily the one sequential search would find first), and the rest of the searches
+  are cancelled. When `all` is `true`, solutions are returned in the same order as by sequential search.
   """
   @spec solve(Csp.t(), Keyword.t()) :: Csp.solve_result()
   def solve(%Csp{} = csp, opts \\ []) do
-    all = Keyword.get(opts, :all, false)
-    ac3 = Keyword.get(opts, :ac3, false)
-    variable_selector = Keyword.get(opts, :variable_selector, :take_head)
+    run(csp, settings(csp, opts))
+  end
+
+  @doc """
+  Same as `solve/2`, but also returns search statistics: `{result, stats}`.
+
+  `stats` is a map with the following keys:
+
+  - `nodes` - the number of values tried for variables (i.e., nodes of the search tree visited)
+  - `backtracks` - the number of tried values that were rejected or led to no solutions
+  - `constraint_checks` - the number of constraint checks performed while testing assignments consistency
+  - `max_depth` - the maximum number of assigned variables reached during search
+  - `checks_per_constraint` - the number of checks of each constraint, in the order of `csp.constraints`.
+  Constraints on the assigned variable are checked in the ascending order of their `Csp.ConstraintCost` estimates,
+  and checking stops at the first violated one, so expensive constraints are usually checked less often.
+  - `elapsed` - the search duration, in microseconds
+  """
+  @spec solve_with_stats(Csp.t(), Keyword.t()) :: {Csp.solve_result(), stats()}
+  def solve_with_stats(%Csp{} = csp, opts \\ []) do
+    counters = :counters.new(map_size(@stats_counters) + length(csp.constraints), [:atomics])
+    {elapsed, result} = :timer.tc(fn -> run(csp, %{settings(csp, opts) | stats: counters}) end)
+
+    checks_per_constraint =
+      csp.constraints
+      |> Enum.with_index(map_size(@stats_counters) + 1)
+      |> Enum.map(fn {_constraint, index} -> :counters.get(counters, index) end)
+
+    stats =
+      @stats_counters
+      |> Enum.map(fn {name, index} -> {name, :counters.get(counters, index)} end)
+      |> Enum.into(%{checks_per_constraint: checks_per_constraint, elapsed: elapsed})
+
+    {result, stats}
+  end
+
+  @doc """
+  Returns a lazy stream of all solutions of `csp`, found with backtracking.
+
+  Solutions are produced one at a time, in the same order as `solve/2` with `all: true`
+  would return them, so you can take the first few of them without searching
+  the rest of the search space:
+
+  ```elixir
+  Csp.Backtracking.stream(csp) |> Enum.take(10)
+  ```
+
+  Supports the same `opts` as `solve/2`, except for `all`.
+  """
+  @spec stream(Csp.t(), Keyword.t()) :: Enumerable.t()
+  def stream(%Csp{} = csp, opts \\ []) do
+    settings = settings(csp, opts)
+
+    case csp.variables do
+      [] -> [%{}]
+      unassigned -> Stream.unfold([frame(%{}, unassigned, csp, settings)], &next_solution(&1, settings))
+    end
+  end
+
+  @doc """
+  Counts all solutions of `csp`, without collecting them in a list.
+
+  Supports the same `opts` as `solve/2`, except for `all`.
+  """
+  @spec count(Csp.t(), Keyword.t()) :: non_neg_integer()
+  def count(%Csp{} = csp, opts \\ []) do
+    stream(csp, opts) |> Enum.count()
+  end
+
+  @doc """
+  Returns a lazy stream of search events, exposing backtracking one decision at a time,
+  e.g., to visualize the search tree or to step through it in a debugger.
 
-    case backtrack(%{}, csp.variables, csp, variable_selector, ac3, all) do
+  The following events are emitted:
+
+  - `{:assign, variable, value}` - `value` is tried for `variable`
+  - `{:wipeout, variable, constraint}` - inference after the last assignment (see `forward_checking` and `ac3` options
+  of `solve/2`) emptied the domain of `variable` because of `constraint`, and a `:reject` event follows.
+  A global constraint can fail without emptying any single domain; then `variable` is the assigned variable
+  - `{:reject, variable, value}` - the assignment of `value` to `variable` is inconsistent
+  (or rejected by inference)
+  - `{:solution, assignment}` - a solution is found; the search continues with the next values
+  - `{:backtrack, variable}` - all values of `variable` were tried, returning to the previously assigned variable
+
+  The stream ends when the whole search space is explored; taking only a part of it doesn't run the rest
+  of the search. Supports the same `opts` as `solve/2`, except for `all` and `parallel`.
+  """
+  @spec steps(Csp.t(), Keyword.t()) :: Enumerable.t()
+  def steps(%Csp{} = csp, opts \\ []) do
+    settings = settings(csp, opts)
+
+    case csp.variables do
+      [] ->
+        [{:solution, %{}}]
+
+      unassigned ->
+        Stream.unfold({[], [frame(%{}, unassigned, csp, settings)]}, fn
+          {[event | events], stack} -> {event, {events, stack}}
+          {[], stack} -> with {events, stack} <- next_step(stack, settings), do: {hd(events), {tl(events), stack}}
+        end)
+    end
+  end
+
+  @doc """
+  Same as `solve/2`, but also returns a trace of the search: `{result, trace}`.
+
+  `trace` is a list of events from `steps/2` as maps, in the order they happened, for replaying or rendering
+  the search elsewhere. Each event has `event` (`:assign`, `:wipeout`, `:reject`, `:solution`, or `:backtrack`)
+  and `depth` (the number of variables assigned by the search, including the one the event is about) keys;
+  `:assign`, `:wipeout`, `:reject`, and `:backtrack` events also have a `variable` key, `:assign` and `:reject`
+  events have a `value` key, `:wipeout` events have a `constraint` key, and `:solution` events have
+  an `assignment` key. The depth of a `:wipeout` event is the depth of the assignment that caused it.
+
+  Unless `all` is `true`, the trace ends at the first solution. Collecting the trace is slower than `solve/2`,
+  so this is meant for debugging and teaching. Supports the same `opts` as `solve/2`, except for `parallel`.
+  """
+  @spec solve_with_trace(Csp.t(), Keyword.t()) :: {Csp.solve_result(), [map()]}
+  def solve_with_trace(%Csp{} = csp, opts \\ []) do
+    events =
+      if Keyword.get(opts, :all, false) do
+        steps(csp, opts)
+      else
+        Stream.transform(steps(csp, opts), false, fn
+          _event, true -> {:halt, true}
+          {:solution, _assignment} = event, false -> {[event], true}
+          event, false -> {[event], false}
+        end)
+      end
+
+    {trace, _path} = Enum.map_reduce(events, [], &trace_event/2)
+
+    result =
+      case for(%{event: :solution, assignment: solution} <- trace, do: solution) do
+        [] -> :no_solution
+        [solution] -> {:solved, solution}
+        solutions -> {:solved, solutions}
+      end
+
+    {result, trace}
+  end
+
+  ## Helpers
+
+  # Converts a `steps/2` event to a trace event; `path` holds variables assigned by the search, most recent first.
+  # A new variable is only selected after its predecessors are assigned, so assigning the most recent variable again
+  # means trying its next value.
+  @spec trace_event(tuple(), [Csp.variable()]) :: {map(), [Csp.variable()]}
+  defp trace_event(event, path)
+
+  defp trace_event({:assign, variable, value}, path) do
+    path =
+      case path do
+        [^variable | _] -> path
+        _ -> [variable | path]
+      end
+
+    {%{event: :assign, variable: variable, value: value, depth: length(path)}, path}
+  end
+
+  defp trace_event({:wipeout, variable, constraint}, path) do
+    {%{event: :wipeout, variable: variable, constraint: constraint, depth: length(path)}, path}
+  end
+
+  defp trace_event({:reject, variable, value}, path) do
+    {%{event: :reject, variable: variable, value: value, depth: length(path)}, path}
+  end
+
+  defp trace_event({:solution, assignment}, path) do
+    {%{event: :solution, assignment: assignment, depth: length(path)}, path}
+  end
+
+  defp trace_event({:backtrack, variable}, [variable | rest] = path) do
+    {%{event: :backtrack, variable: variable, depth: length(path)}, rest}
+  end
+
+  # a variable without values to try (e.g., with an empty domain) is never assigned, so it's not on the path
+  defp trace_event({:backtrack, variable}, path) do
+    {%{event: :backtrack, variable: variable, depth: length(path) + 1}, path}
+  end
+
+  @spec run(Csp.t(), settings()) :: Csp.solve_result()
+  defp run(csp, settings) do
+    solutions =
+      if settings.parallel do
+        parallel_backtrack(csp, settings)
+      else
+        backtrack(%{}, csp.variables, csp, settings)
+      end
+
+    case solutions do
       [] -> :no_solution
       [solution] -> {:solved, solution}
       solutions when is_list(solutions) -> {:solved, solutions}
     end
   end
 
-  ## Helpers
+  @spec settings(Csp.t(), Keyword.t()) :: settings()
+  defp settings(csp, opts) do
+    %{
+      variable_selector: Keyword.get(opts, :variable_selector, :take_head),
+      value_selector: Keyword.get(opts, :value_selector, :in_order),
+      ac3: Keyword.get(opts, :ac3, false),
+      forward_checking: Keyword.get(opts, :forward_checking, false),
+      all: Keyword.get(opts, :all, false),
+      parallel: Keyword.get(opts, :parallel, false),
+      prune: Keyword.get(opts, :prune),
+      constraints_index: constraints_index(csp),
+      stats: nil
+    }
+  end
 
-  @spec backtrack(Csp.assignment(), [Csp.variable()], Csp.t(), variable_selector(), boolean(), boolean()) ::
-          [Csp.assignment()]
-  defp backtrack(assignment, unassigned_variables, csp, variable_selector, ac3, all)
+  # Like `Csp.constraints_index/1`, but the constraints on each variable are ordered by their estimated costs,
+  # and paired with the indexes of the counters of their checks.
+  @spec constraints_index(Csp.t()) :: %{Csp.variable() => [{Constraint.t(), counter :: pos_integer()}]}
+  defp constraints_index(csp) do
+    csp.constraints
+    |> Enum.with_index(map_size(@stats_counters) + 1)
+    |> Enum.sort_by(fn {constraint, _counter} -> ConstraintCost.estimate(constraint) end)
+    |> Enum.reverse()
+    |> Enum.reduce(%{}, fn {constraint, _counter} = entry, index ->
+      Constraint.arguments(constraint)
+      |> Enum.uniq()
+      |> Enum.reduce(index, fn variable, index -> Map.update(index, variable, [entry], &[entry | &1]) end)
+    end)
+  end
+
+  @spec increment(settings(), :nodes | :backtracks) :: :ok
+  defp increment(%{stats: nil}, _counter), do: :ok
+  defp increment(%{stats: counters}, counter), do: :counters.add(counters, @stats_counters[counter], 1)
 
-  defp backtrack(assignment, [] = _unassigned, _, _, _, _), do: [assignment]
+  @spec count_check(settings(), pos_integer()) :: :ok
+  defp count_check(%{stats: nil}, _counter), do: :ok
 
-  defp backtrack(assignment, [unassigned_variable | rest], csp, :take_head, ac3, all) do
-    backtrack_variable_selected(assignment, {unassigned_variable, rest}, csp, :take_head, ac3, all)
+  defp count_check(%{stats: counters}, counter) do
+    :counters.add(counters, @stats_counters.constraint_checks, 1)
+    :counters.add(counters, counter, 1)
   end
 
-  defp backtrack(assignment, unassigned_variables, csp, :minimum_remaining_values, ac3, all) do
-    {min_domain_values_variable, _domain} =
-      Map.take(csp.domains, unassigned_variables)
-      |> Enum.map(fn {variable, domain} ->
-        {variable, length(domain)}
-      end)
-      |> Enum.min(fn {_, domain_length}, {_, domain_length2} ->
-        domain_length <= domain_length2
+  @spec visit(settings(), Csp.assignment()) :: :ok
+  defp visit(%{stats: nil}, _assignment), do: :ok
+
+  defp visit(%{stats: counters} = settings, assignment) do
+    increment(settings, :nodes)
+
+    if map_size(assignment) > :counters.get(counters, @stats_counters.max_depth) do
+      :counters.put(counters, @stats_counters.max_depth, map_size(assignment))
+    else
+      :ok
+    end
+  end
+
+  @spec frame(Csp.assignment(), [Csp.variable()], Csp.t(), settings()) :: frame()
+  defp frame(assignment, unassigned_variables, csp, settings) do
+    {variable, unassigned} = select_variable(unassigned_variables, csp, settings)
+
+    {assignment, variable, order_values(csp, variable, unassigned, settings), unassigned, csp}
+  end
+
+  @spec next_solution([frame()], settings()) :: {Csp.assignment(), [frame()]} | nil
+  defp next_solution(stack, settings)
+
+  defp next_solution([], _settings), do: nil
+
+  defp next_solution([{_assignment, _variable, [], _unassigned, _csp} | stack], settings) do
+    next_solution(stack, settings)
+  end
+
+  defp next_solution([{assignment, variable, [value | values], unassigned, csp} | stack], settings) do
+    stack = [{assignment, variable, values, unassigned, csp} | stack]
+    assignment = Map.put(assignment, variable, value)
+    visit(settings, assignment)
+
+    with true <- consistent?(assignment, [variable], settings),
+         {:ok, csp, assignment, unassigned} <- infer(csp, variable, assignment, unassigned, settings) do
+      case unassigned do
+        [] -> {assignment, stack}
+        _ -> next_solution([frame(assignment, unassigned, csp, settings) | stack], settings)
+      end
+    else
+      _ ->
+        increment(settings, :backtracks)
+        next_solution(stack, settings)
+    end
+  end
+
+  @spec next_step([frame()], settings()) :: {events :: [tuple()], [frame()]} | nil
+  defp next_step(stack, settings)
+
+  defp next_step([], _settings), do: nil
+
+  defp next_step([{_assignment, variable, [], _unassigned, _csp} | stack], _settings) do
+    {[{:backtrack, variable}], stack}
+  end
+
+  defp next_step([{assignment, variable, [value | values], unassigned, csp} | stack], settings) do
+    stack = [{assignment, variable, values, unassigned, csp} | stack]
+    assignment = Map.put(assignment, variable, value)
+
+    with true <- consistent?(assignment, [variable], settings),
+         {:ok, csp, assignment, unassigned} <- infer(csp, variable, assignment, unassigned, settings) do
+      case unassigned do
+        [] -> {[{:assign, variable, value}, {:solution, assignment}], stack}
+        _ -> {[{:assign, variable, value}], [frame(assignment, unassigned, csp, settings) | stack]}
+      end
+    else
+      {:no_solution, {wiped_out, constraint}} ->
+        {[{:assign, variable, value}, {:wipeout, wiped_out, constraint}, {:reject, variable, value}], stack}
+
+      _ ->
+        {[{:assign, variable, value}, {:reject, variable, value}], stack}
+    end
+  end
+
+  @spec parallel_backtrack(Csp.t(), settings()) :: [Csp.assignment()]
+  defp parallel_backtrack(%Csp{variables: []}, _settings), do: [%{}]
+
+  defp parallel_backtrack(csp, settings) do
+    {variable, unassigned} = select_variable(csp.variables, csp, settings)
+
+    subtrees_solutions =
+      order_values(csp, variable, unassigned, settings)
+      |> Task.async_stream(
+        fn value ->
+          csp = %{csp | domains: Map.put(csp.domains, variable, [value])}
+          backtrack_variable_selected(%{}, {variable, unassigned}, csp, settings)
+        end,
+        ordered: settings.all,
+        timeout: :infinity
+      )
+      |> Stream.map(fn {:ok, solutions} -> solutions end)
+
+    if settings.all do
+      Enum.concat(subtrees_solutions)
+    else
+      # halting the stream cancels the searches that are still running
+      Enum.find(subtrees_solutions, [], fn solutions -> solutions != [] end)
+    end
+  end
+
+  @spec backtrack(Csp.assignment(), [Csp.variable()], Csp.t(), settings()) :: [Csp.assignment()]
+  defp backtrack(assignment, unassigned_variables, csp, settings)
+
+  defp backtrack(assignment, [] = _unassigned, _, _), do: [assignment]
+
+  defp backtrack(assignment, unassigned_variables, csp, settings) do
+    backtrack_variable_selected(assignment, select_variable(unassigned_variables, csp, settings), csp, settings)
+  end
+
+  @spec select_variable([Csp.variable()], Csp.t(), settings()) :: {Csp.variable(), [Csp.variable()]}
+  defp select_variable(unassigned_variables, csp, settings)
+
+  defp select_variable([unassigned_variable | rest], _csp, %{variable_selector: :take_head}) do
+    {unassigned_variable, rest}
+  end
+
+  defp select_variable(unassigned_variables, csp, %{variable_selector: :minimum_remaining_values}) do
+    domain_lengths =
+      Enum.map(unassigned_variables, fn variable ->
+        {variable, length(Map.fetch!(csp.domains, variable))}
       end)
 
-    {unassigned_variable, rest} =
-      {min_domain_values_variable, List.delete(unassigned_variables, min_domain_values_variable)}
+    {_, min_domain_length} = Enum.min_by(domain_lengths, fn {_variable, domain_length} -> domain_length end)
+    candidates = for {variable, ^min_domain_length} <- domain_lengths, do: variable
+
+    min_domain_values_variable =
+      case candidates do
+        [variable] -> variable
+        _ -> max_degree_variable(candidates, csp, unassigned_variables)
+      end
 
-    backtrack_variable_selected(assignment, {unassigned_variable, rest}, csp, :minimum_remaining_values, ac3, all)
+    {min_domain_values_variable, List.delete(unassigned_variables, min_domain_values_variable)}
   end
 
-  defp backtrack(assignment, unassigned_variables, csp, variable_selector, run_ac3, all) do
-    {unassigned_variable, rest} = variable_selector.(unassigned_variables)
+  defp select_variable(unassigned_variables, csp, %{variable_selector: :degree}) do
+    max_degree_variable = max_degree_variable(unassigned_variables, csp, unassigned_variables)
 
-    backtrack_variable_selected(assignment, {unassigned_variable, rest}, csp, variable_selector, run_ac3, all)
+    {max_degree_variable, List.delete(unassigned_variables, max_degree_variable)}
   end
 
-  defp backtrack_variable_selected(assignment, {variable, unassigned}, csp, variable_selector, ac3, all) do
-    domain = Map.fetch!(csp.domains, variable)
+  defp select_variable(unassigned_variables, csp, %{variable_selector: variable_selector})
+       when is_function(variable_selector, 2) do
+    variable_selector.(unassigned_variables, csp)
+  end
+
+  defp select_variable(unassigned_variables, _csp, %{variable_selector: variable_selector}) do
+    variable_selector.(unassigned_variables)
+  end
+
+  defp backtrack_variable_selected(assignment, {variable, unassigned}, csp, settings) do
+    domain = order_values(csp, variable, unassigned, settings)
 
     Enum.reduce_while(domain, [], fn value, acc ->
       assignment = Map.put(assignment, variable, value)
+      visit(settings, assignment)
+
+      if consistent?(assignment, [variable], settings) do
+        case infer(csp, variable, assignment, unassigned, settings) do
+          {:ok, csp, assignment, unassigned} ->
+            future_result = backtrack(assignment, unassigned, csp, settings)
 
-      if Csp.consistent?(csp, assignment) do
-        {inconsistent, csp, assignment, unassigned} =
-          if ac3 do
-            case AC3.reduce(csp, assignment, unassigned) do
-              {:ok, csp, assignment, unassigned} -> {false, csp, assignment, unassigned}
-              :no_solution -> {true, csp, assignment, unassigned}
+            case future_result do
+              [] ->
+                increment(settings, :backtracks)
+                {:cont, acc}
+
+              solutions when is_list(solutions) ->
+                if settings.all, do: {:cont, acc ++ solutions}, else: {:halt, solutions}
             end
-          else
-            {false, csp, assignment, unassigned}
-          end
 
-        if inconsistent do
-          {:cont, acc}
+          {:no_solution, _wipeout} ->
+            increment(settings, :backtracks)
+            {:cont, acc}
+        end
+      else
+        increment(settings, :backtracks)
+        {:cont, acc}
+      end
+    end)
+  end
+
+  # Returns the variable from `candidates` participating in the most constraints with other
+  # `unassigned_variables`; ties are broken by the order of `candidates`, so the selection is deterministic.
+  @spec max_degree_variable([Csp.variable()], Csp.t(), [Csp.variable()]) :: Csp.variable()
+  defp max_degree_variable(candidates, csp, unassigned_variables) do
+    Enum.max_by(candidates, fn variable ->
+      Csp.constraints_on(csp, variable)
+      |> Enum.count(fn constraint ->
+        Constraint.arguments(constraint)
+        |> Enum.any?(fn argument -> argument != variable and argument in unassigned_variables end)
+      end)
+    end)
+  end
+
+  # Checks only the constraints on `variables`, since the rest of `assignment`
+  # is already known to be consistent.
+  @spec consistent?(Csp.assignment(), [Csp.variable()], settings()) :: boolean()
+  defp consistent?(assignment, variables, settings) do
+    Enum.all?(variables, fn variable ->
+      Map.get(settings.constraints_index, variable, [])
+      |> Enum.all?(fn {constraint, counter} ->
+        arguments = Constraint.arguments(constraint)
+
+        if Enum.all?(arguments, &Map.has_key?(assignment, &1)) do
+          count_check(settings, counter)
+          Constraint.satisfies?(constraint, assignment)
         else
-          future_result = backtrack(assignment, unassigned, csp, variable_selector, ac3, all)
+          # if we don't have all required assignments to check the constraint, skip it
+          true
+        end
+      end)
+    end)
+  end
+
+  @spec order_values(Csp.t(), Csp.variable(), [Csp.variable()], settings()) :: Csp.domain()
+  defp order_values(csp, variable, unassigned, settings)
+
+  defp order_values(csp, variable, _unassigned, %{value_selector: :in_order}), do: Map.fetch!(csp.domains, variable)
+
+  defp order_values(csp, variable, _unassigned, %{value_selector: selector}) when is_function(selector, 2) do
+    selector.(variable, Map.fetch!(csp.domains, variable))
+  end
+
+  defp order_values(csp, variable, unassigned, %{value_selector: :least_constraining_value}) do
+    arcs = binary_neighbours(csp, variable, unassigned)
+
+    Map.fetch!(csp.domains, variable)
+    |> Enum.sort_by(fn value ->
+      Enum.reduce(arcs, 0, fn {neighbour, constraint}, ruled_out ->
+        ruled_out +
+          Enum.count(Map.fetch!(csp.domains, neighbour), fn neighbour_value ->
+            !Constraint.satisfies?(constraint, %{variable => value, neighbour => neighbour_value})
+          end)
+      end)
+    end)
+  end
+
+  # Returns `{:no_solution, nil}` if the assignment is rejected without emptying any domain.
+  @spec infer(Csp.t(), Csp.variable(), Csp.assignment(), [Csp.variable()], settings()) :: inference()
+  defp infer(csp, variable, assignment, unassigned, settings) do
+    if settings.prune != nil and settings.prune.(assignment) do
+      {:no_solution, nil}
+    else
+      propagate(csp, variable, assignment, unassigned, settings)
+    end
+  end
+
+  @spec propagate(Csp.t(), Csp.variable(), Csp.assignment(), [Csp.variable()], settings()) :: inference()
+  defp propagate(csp, variable, assignment, unassigned, settings) do
+    forward_checked =
+      cond do
+        settings.forward_checking -> forward_check(csp, variable, assignment, unassigned)
+        settings.ac3 -> prune_global_constraints(csp, variable, assignment)
+        true -> {:ok, csp}
+      end
+
+    case forward_checked do
+      {:ok, csp} ->
+        if settings.ac3 do
+          # pin the assigned values, so AC-3 prunes neighbours by them and never reassigns assigned variables
+          pinned_domains = Map.new(assignment, fn {variable, value} -> {variable, [value]} end)
+          csp = %{csp | domains: Map.merge(csp.domains, pinned_domains)}
+
+          case AC3.reduce(csp, assignment, unassigned) do
+            {:ok, csp, reduced_assignment, unassigned} ->
+              # AC-3 only checks unary and binary constraints, and may also assign variables whose
+              # domains it reduced to a single value, so we need to check constraints on all changed variables
+              inferred_variables =
+                for {variable, value} <- reduced_assignment, Map.fetch(assignment, variable) != {:ok, value} do
+                  variable
+                end
 
-          case future_result do
-            [] ->
-              {:cont, acc}
+              if consistent?(reduced_assignment, inferred_variables, settings) do
+                {:ok, csp, reduced_assignment, unassigned}
+              else
+                {:no_solution, nil}
+              end
 
-            solutions when is_list(solutions) ->
-              if all, do: {:cont, acc ++ solutions}, else: {:halt, solutions}
+            {:no_solution, wipeout} ->
+              {:no_solution, wipeout}
           end
+        else
+          {:ok, csp, assignment, unassigned}
         end
-      else
-        {:cont, acc}
+
+      {:no_solution, wipeout} ->
+        {:no_solution, wipeout}
+    end
+  end
+
+  # Removes values incompatible with the `variable`'s assigned value from the domains
+  # of `unassigned` variables that share a binary constraint with `variable`,
+  # and prunes the domains of variables sharing a global constraint with it.
+  @spec forward_check(Csp.t(), Csp.variable(), Csp.assignment(), [Csp.variable()]) ::
+          {:ok, Csp.t()} | {:no_solution, AC3.wipeout()}
+  defp forward_check(csp, variable, assignment, unassigned) do
+    value = Map.fetch!(assignment, variable)
+
+    binary_neighbours(csp, variable, unassigned)
+    |> Enum.reduce_while({:ok, csp}, fn {neighbour, constraint}, {:ok, csp} ->
+      reduced_domain =
+        Map.fetch!(csp.domains, neighbour)
+        |> Enum.filter(fn neighbour_value ->
+          Constraint.satisfies?(constraint, %{variable => value, neighbour => neighbour_value})
+        end)
+
+      case reduced_domain do
+        [] -> {:halt, {:no_solution, {neighbour, constraint}}}
+        _ -> {:cont, {:ok, %{csp | domains: Map.put(csp.domains, neighbour, reduced_domain)}}}
+      end
+    end)
+    |> case do
+      {:ok, csp} -> prune_global_constraints(csp, variable, assignment)
+      {:no_solution, wipeout} -> {:no_solution, wipeout}
+    end
+  end
+
+  # Enforces generalized arc consistency of global constraints on `variable` with their `prune/2` functions
+  # (see `@pruned_constraints`), treating assigned variables as having singleton domains.
+  # A constraint can fail without emptying any single domain, so the wipeout names the assigned `variable`.
+  @spec prune_global_constraints(Csp.t(), Csp.variable(), Csp.assignment()) ::
+          {:ok, Csp.t()} | {:no_solution, AC3.wipeout()}
+  defp prune_global_constraints(csp, variable, assignment) do
+    Csp.constraints_on(csp, variable)
+    |> Enum.filter(&match?(%module{} when module in @pruned_constraints, &1))
+    |> Enum.reduce_while({:ok, csp}, fn %module{} = constraint, {:ok, csp} ->
+      domains =
+        Map.new(Constraint.arguments(constraint), fn argument ->
+          case Map.fetch(assignment, argument) do
+            {:ok, value} -> {argument, [value]}
+            :error -> {argument, Map.fetch!(csp.domains, argument)}
+          end
+        end)
+
+      case module.prune(constraint, domains) do
+        {:ok, pruned_domains} ->
+          unassigned_domains = Map.drop(pruned_domains, Map.keys(assignment))
+          {:cont, {:ok, %{csp | domains: Map.merge(csp.domains, unassigned_domains)}}}
+
+        :no_solution ->
+          {:halt, {:no_solution, {variable, constraint}}}
+      end
+    end)
+  end
+
+  # Returns a list of `{neighbour, constraint}` tuples for all binary constraints
+  # connecting `variable` with one of the `unassigned` variables.
+  @spec binary_neighbours(Csp.t(), Csp.variable(), [Csp.variable()]) :: [{Csp.variable(), Constraint.t()}]
+  defp binary_neighbours(csp, variable, unassigned) do
+    Csp.constraints_on(csp, variable)
+    |> Enum.flat_map(fn constraint ->
+      case Constraint.arguments(constraint) do
+        [x, y] ->
+          neighbour = if x == variable, do: y, else: x
+          if neighbour in unassigned, do: [{neighbour, constraint}], else: []
+
+        _ ->
+          []
       end
     end)
   end
@@ -3,7 +3,7 @@ defmodule Csp.CLI do
   Command line interface for constraint satisfaction.
   """
   alias Csp
-  alias Csp.Problems
+  alias Csp.{Problems, Parser}
   alias Csp.AC3
 
   @doc """
@@ -13,15 +13,96 @@ defmodule Csp.CLI do
   $ mix escript.build
   $ ./csp
   ```
+
+  Alternatively, pass a path to a problem file (see `Csp.Parser` for the format),
+  or `-` to read the problem from the standard input, to solve it and print the solution:
+
+  ```
+  $ ./csp problem.csp
+  $ cat problem.csp | ./csp -
+  ```
+
+  For example, `./csp examples/digits.csp` prints the only solution of the problem defined in
+  `examples/digits.csp`.
+
+  When solving a problem, the escript exits with status `0` if a solution is printed, `1` if the problem
+  has no solution, and `2` if the problem cannot be read or parsed (errors are printed to the standard error).
   """
-  def main(_args) do
-    IO.puts("Let's try out some example constraint satisfaction problems.")
+  def main(args) do
+    case args do
+      [flag] when flag in ["-h", "--help"] ->
+        IO.puts(usage())
 
-    trial_problem_selection()
+      [path] ->
+        System.halt(solve_problem(path))
+
+      [] ->
+        IO.puts("Let's try out some example constraint satisfaction problems.")
+
+        trial_problem_selection()
+
+      _ ->
+        IO.puts(:stderr, usage())
+        System.halt(2)
+    end
   end
 
   ## Helpers
 
+  defp usage() do
+    """
+    Usage:
+      csp              try out example problems interactively
+      csp PATH         solve the problem defined in the file at PATH
+      csp -            solve the problem read from the standard input
+
+    Exits with 0 if a solution is found, 1 if there is no solution, and 2 on invalid input.\
+    """
+  end
+
+  # Solves the problem at `path` (or standard input for `-`), returning the exit status.
+  defp solve_problem(path) do
+    with {:ok, input} <- read_problem(path),
+         {:ok, csp} <- Parser.parse(input) do
+      case Csp.solve(csp) do
+        {:solved, solution} ->
+          IO.puts(Csp.format_assignment(solution))
+          0
+
+        :no_solution ->
+          IO.puts("no solution")
+          1
+      end
+    else
+      {:error, reason} when is_atom(reason) ->
+        IO.puts(:stderr, "Cannot read #{path}: #{:file.format_error(reason)}")
+        2
+
+      {:error, message} ->
+        IO.puts(:stderr, "Cannot parse #{path}: #{message}")
+        2
+    end
+  end
+
+  defp read_problem(path) do
+    input =
+      case path do
+        "-" ->
+          case IO.read(:stdio, :all) do
+            {:error, reason} -> {:error, reason}
+            input -> {:ok, input}
+          end
+
+        path ->
+          File.read(path)
+      end
+
+    case input do
+      {:ok, input} -> if String.trim(input) == "", do: {:error, "the problem is empty"}, else: {:ok, input}
+      error -> error
+    end
+  end
+
   defp trial_problem_selection() do
     IO.puts("Select a trial problem (type `1`, `2`, ...), or terminate (type `q`):")
     IO.puts("\t1. Sudoku")
@@ -14,6 +14,9 @@ defprotocol Csp.Constraint do
   ```
   {[:a, :b], fn a, b -> a != b end}
   ```
+
+  Constraints that are expensive to check can also implement `Csp.ConstraintCost`,
+  so that search checks them after the cheaper ones.
   """
 
   @type t :: any
@@ -0,0 +1,23 @@
+defprotocol Csp.ConstraintCost do
+  @moduledoc """
+  Estimates how expensive it is to check a constraint with `Csp.Constraint.satisfies?/2`.
+
+  `Csp.Backtracking` checks the constraints on a newly assigned variable in the ascending order of their costs,
+  so an inconsistent assignment is usually rejected by a cheap constraint before the expensive ones are checked.
+
+  Implementing this protocol is optional: by default, the cost of a constraint is the number of its arguments,
+  so only constraints doing much more work per argument (e.g., scanning a table of allowed tuples) need it.
+  """
+  @fallback_to_any true
+
+  @doc """
+  Returns the estimated cost of checking `constraint`, in arbitrary units.
+  """
+  @spec estimate(t) :: non_neg_integer()
+  def estimate(constraint)
+end
+
+defimpl Csp.ConstraintCost, for: Any do
+  @spec estimate(any) :: non_neg_integer()
+  def estimate(constraint), do: length(Csp.Constraint.arguments(constraint))
+end
@@ -3,6 +3,24 @@ defmodule Csp.Constraints do
   Defines commonly used constraints.
   """
   alias Csp.Constraint
+  alias Csp.Constraints.{
+    AbsDiff,
+    AllDifferent,
+    And,
+    Cardinality,
+    Channeling,
+    Clause,
+    Cumulative,
+    Element,
+    LexLeq,
+    Linear,
+    Mod,
+    Not,
+    NValues,
+    Or,
+    Reified,
+    Table
+  }
 
   @doc """
   Creates a list of binary constraints that define that all variables
@@ -21,4 +39,194 @@ defmodule Csp.Constraints do
   end
 
   def all_different_constraints([]), do: []
+
+  @doc """
+  Creates a binary constraint that defines that variables `x` and `y` have different values.
+  """
+  @spec not_equal(Csp.variable(), Csp.variable()) :: Constraint.t()
+  def not_equal(x, y), do: {[x, y], fn [x_value, y_value] -> x_value != y_value end}
+
+  @doc """
+  Creates a unary constraint that defines that `variable` is equal to the constant `value`.
+  """
+  @spec equal_to(Csp.variable(), Csp.value()) :: Constraint.t()
+  def equal_to(variable, value), do: {[variable], fn [variable_value] -> variable_value == value end}
+
+  @doc """
+  Creates a list of binary constraints that define that all variables
+  in the `variables` list are equal (each variable is constrained to be equal to the next one).
+  """
+  @spec all_equal_constraints([Csp.variable()]) :: [Constraint.t()]
+  def all_equal_constraints(variables) do
+    Enum.zip(variables, Enum.drop(variables, 1))
+    |> Enum.map(fn {variable, next_variable} ->
+      {[variable, next_variable], fn [variable, next_variable] -> variable == next_variable end}
+    end)
+  end
+
+  @doc """
+  Creates a single global constraint that defines that all variables
+  in the `variables` list are different.
+
+  See `Csp.Constraints.AllDifferent`.
+  """
+  @spec all_different([Csp.variable()]) :: AllDifferent.t()
+  def all_different(variables), do: %AllDifferent{variables: variables}
+
+  @doc """
+  Creates a linear arithmetic constraint `sum(coefficient * variable) <op> rhs`
+  from a keyword list (or a list of tuples) of `{variable, coefficient}` `terms`.
+
+  See `Csp.Constraints.Linear`.
+  """
+  @spec linear([{Csp.variable(), number()}], Linear.op(), number()) :: Linear.t()
+  def linear(terms, op, rhs) when op in [:<, :<=, :==, :>=, :>, :!=] do
+    %Linear{terms: terms, op: op, rhs: rhs}
+  end
+
+  @doc """
+  Creates a table constraint, allowing only the listed `tuples` of values for `variables`.
+
+  See `Csp.Constraints.Table`.
+  """
+  @spec table([Csp.variable()], [[Csp.value()]]) :: Table.t()
+  def table(variables, tuples), do: %Table{variables: variables, tuples: tuples}
+
+  @doc """
+  Creates a constraint requiring values of `left` variables to be lexicographically
+  less than or equal to values of `right` variables.
+
+  See `Csp.Constraints.LexLeq`.
+  """
+  @spec lex_leq([Csp.variable()], [Csp.variable()]) :: LexLeq.t()
+  def lex_leq(left, right) when length(left) == length(right), do: %LexLeq{left: left, right: right}
+
+  @doc """
+  Creates a propositional clause, satisfied if at least one of the `{variable, polarity}` `literals`
+  has its variable assigned to `polarity`.
+
+  See `Csp.Constraints.Clause`.
+  """
+  @spec clause([Clause.literal()]) :: Clause.t()
+  def clause(literals), do: %Clause{literals: literals}
+
+  @doc """
+  Creates a channeling constraint between two viewpoints of a permutation: `x[i] == j` if and only if `y[j] == i`.
+
+  See `Csp.Constraints.Channeling`.
+  """
+  @spec channeling([Csp.variable()], [Csp.variable()]) :: Channeling.t()
+  def channeling(x, y) when length(x) == length(y), do: %Channeling{x: x, y: y}
+
+  @doc """
+  Creates a constraint that is satisfied when `constraint` is not.
+  The negation is only checked once all arguments of `constraint` are assigned.
+
+  See `Csp.Constraints.Not`.
+  """
+  @spec negate(Constraint.t()) :: Not.t()
+  def negate(constraint), do: %Not{constraint: constraint}
+
+  @doc """
+  Creates a constraint that is satisfied when all of the `constraints` are satisfied.
+
+  See `Csp.Constraints.And`.
+  """
+  @spec conjunction([Constraint.t()]) :: And.t()
+  def conjunction(constraints), do: %And{constraints: constraints}
+
+  @doc """
+  Creates a constraint that is satisfied when at least one of the `constraints` is satisfied.
+
+  See `Csp.Constraints.Or`.
+  """
+  @spec disjunction([Constraint.t()]) :: Or.t()
+  def disjunction(constraints), do: %Or{constraints: constraints}
+
+  @doc """
+  Creates a constraint requiring the `indicator` variable to be `1` when `constraint` is satisfied, and `0` otherwise.
+
+  See `Csp.Constraints.Reified`.
+  """
+  @spec reified(Csp.variable(), Constraint.t()) :: Reified.t()
+  def reified(indicator, constraint), do: %Reified{indicator: indicator, constraint: constraint}
+
+  @doc """
+  Creates a constraint requiring `result` to be equal to the variable of `array` at (0-based) position `index`.
+
+  See `Csp.Constraints.Element`.
+  """
+  @spec element(Csp.variable(), [Csp.variable()], Csp.variable()) :: Element.t()
+  def element(index, array, result), do: %Element{index: index, array: array, result: result}
+
+  @doc """
+  Creates a cumulative constraint, requiring the total demand of tasks running at the same time
+  to not exceed `capacity`. Task `i` starts at the time held by `Enum.at(starts, i)` variable,
+  and runs for `Enum.at(durations, i)`, using `Enum.at(demands, i)` of the resource.
+
+  See `Csp.Constraints.Cumulative`.
+  """
+  @spec cumulative([Csp.variable()], [non_neg_integer()], [non_neg_integer()], non_neg_integer()) :: Cumulative.t()
+  def cumulative(starts, durations, demands, capacity)
+      when length(starts) == length(durations) and length(starts) == length(demands) do
+    %Cumulative{starts: starts, durations: durations, demands: demands, capacity: capacity}
+  end
+
+  @doc """
+  Creates a binary constraint requiring `abs(x - y) == k` for integer variables `x` and `y`.
+
+  See `Csp.Constraints.AbsDiff`.
+  """
+  @spec abs_diff(Csp.variable(), Csp.variable(), non_neg_integer()) :: AbsDiff.t()
+  def abs_diff(x, y, k) when is_integer(k) and k >= 0, do: %AbsDiff{x: x, y: y, k: k}
+
+  @doc """
+  Creates a unary constraint requiring `Integer.mod(variable, modulus) == remainder` for an integer `variable`.
+
+  See `Csp.Constraints.Mod`.
+  """
+  @spec mod(Csp.variable(), integer(), integer()) :: Mod.t()
+  def mod(variable, modulus, remainder) when is_integer(modulus) and modulus != 0 do
+    %Mod{variable: variable, modulus: modulus, remainder: remainder}
+  end
+
+  @doc """
+  Creates a constraint requiring at most `count` of `variables` to be assigned to `value`.
+
+  See `Csp.Constraints.Cardinality`.
+  """
+  @spec at_most([Csp.variable()], Csp.value(), non_neg_integer()) :: Cardinality.t()
+  def at_most(variables, value, count) when is_integer(count) and count >= 0 do
+    %Cardinality{variables: variables, value: value, max: count}
+  end
+
+  @doc """
+  Creates a constraint requiring at least `count` of `variables` to be assigned to `value`.
+
+  See `Csp.Constraints.Cardinality`.
+  """
+  @spec at_least([Csp.variable()], Csp.value(), non_neg_integer()) :: Cardinality.t()
+  def at_least(variables, value, count) when is_integer(count) and count >= 0 do
+    %Cardinality{variables: variables, value: value, min: count}
+  end
+
+  @doc """
+  Creates a constraint requiring exactly `count` of `variables` to be assigned to `value`.
+
+  See `Csp.Constraints.Cardinality`.
+  """
+  @spec exactly([Csp.variable()], Csp.value(), non_neg_integer()) :: Cardinality.t()
+  def exactly(variables, value, count) when is_integer(count) and count >= 0 do
+    %Cardinality{variables: variables, value: value, min: count, max: count}
+  end
+
+  @doc """
+  Creates a constraint comparing the number of distinct values of `variables` with `count` using `op`.
+
+  See `Csp.Constraints.NValues`.
+  """
+  @spec n_values([Csp.variable()], Linear.op(), non_neg_integer()) :: NValues.t()
+  def n_values(variables, op, count) when op in [:<, :<=, :==, :>=, :>, :!=] do
+    %NValues{variables: variables, op: op, count: count}
+  end
 end
@@ -0,0 +1,25 @@
+defmodule Csp.Constraints.AbsDiff do
+  @moduledoc """
+  A binary arithmetic constraint requiring the absolute difference of integer variables `x` and `y`
+  to be equal to the constant `k`: `abs(x - y) == k`.
+
+  The constraint is considered satisfied while `x` or `y` is not assigned yet.
+  """
+
+  @type t :: %__MODULE__{x: Csp.variable(), y: Csp.variable(), k: non_neg_integer()}
+
+  defstruct [:x, :y, :k]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.AbsDiff.t()) :: [Csp.variable()]
+    def arguments(%{x: x, y: y}), do: [x, y]
+
+    @spec satisfies?(Csp.Constraints.AbsDiff.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{x: x, y: y, k: k}, assignment) do
+      case {Map.fetch(assignment, x), Map.fetch(assignment, y)} do
+        {{:ok, x_value}, {:ok, y_value}} -> abs(x_value - y_value) == k
+        _ -> true
+      end
+    end
+  end
+end
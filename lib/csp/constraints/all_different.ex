@@ -0,0 +1,203 @@
+defmodule Csp.Constraints.AllDifferent do
+  @moduledoc """
+  A global constraint requiring all of its `variables` to take different values.
+
+  Compared to the list of binary constraints generated by `Csp.Constraints.all_different_constraints/1`,
+  this is a single constraint, checked by looking at all `variables` at once.
+  Variables that are not assigned yet are ignored, so the constraint can also be checked
+  against partial assignments.
+
+  Backtracking with forward checking or AC-3 also prunes domains with `prune/2`, which is stronger
+  than pruning with binary inequalities: e.g., it detects that three variables sharing two values
+  can't be all different before any of them is assigned.
+  """
+
+  @type t :: %__MODULE__{variables: [Csp.variable()]}
+
+  # matches values to variables
+  @typep matching :: %{Csp.value() => Csp.variable()}
+  @typep graph_node :: {:variable, Csp.variable()} | {:value, Csp.value()}
+  # directs edges from each node to a list of its successors
+  @typep graph :: %{graph_node() => [graph_node()]}
+
+  defstruct [:variables]
+
+  @doc """
+  Enforces generalized arc consistency of the all different `constraint` on `domains`,
+  a map from each of the constraint's variables to its domain.
+
+  A value is kept in a variable's domain only if there is an assignment of all variables to different values
+  from their domains that assigns it to the variable. Such assignments are maximum matchings in the bipartite graph
+  of variables and values, and this is Régin's filtering algorithm: one maximum matching is found
+  with Kuhn's augmenting paths algorithm, and a value is kept if it's matched to the variable, or if the edge
+  between them lies on an alternating cycle (the variable and the value are in the same strongly connected component
+  of the graph with matched edges directed from variables to values and the rest from values to variables),
+  or on an alternating path starting from an unmatched value.
+
+  For `n` variables and `e` pairs of a variable and a value in its domain, finding the matching takes `O(n * e)` time,
+  and the rest of the filtering takes `O(e)` time.
+
+  Returns `{:ok, pruned_domains}`, or `:no_solution` if the variables can't take different values.
+  """
+  @spec prune(t(), %{Csp.variable() => Csp.domain()}) :: {:ok, %{Csp.variable() => Csp.domain()}} | :no_solution
+  def prune(%__MODULE__{variables: variables}, domains) do
+    variables = Enum.uniq(variables)
+
+    case maximum_matching(variables, domains) do
+      {:ok, matching} ->
+        successors = residual_graph(variables, domains, matching)
+        components = strongly_connected_components(Enum.map(variables, &{:variable, &1}), successors)
+        free_values = for {:value, value} = node <- Map.keys(successors), !Map.has_key?(matching, value), do: node
+        reachable = reachable(free_values, successors, MapSet.new())
+
+        pruned_domains =
+          Map.new(variables, fn variable ->
+            component = Map.fetch!(components, {:variable, variable})
+
+            domain =
+              Enum.filter(Map.fetch!(domains, variable), fn value ->
+                Map.get(matching, value) == variable or Map.get(components, {:value, value}) == component or
+                  MapSet.member?(reachable, {:value, value})
+              end)
+
+            {variable, domain}
+          end)
+
+        {:ok, pruned_domains}
+
+      :error ->
+        :no_solution
+    end
+  end
+
+  ## Helpers
+
+  @spec maximum_matching([Csp.variable()], %{Csp.variable() => Csp.domain()}) :: {:ok, matching()} | :error
+  defp maximum_matching(variables, domains) do
+    Enum.reduce_while(variables, {:ok, %{}}, fn variable, {:ok, matching} ->
+      case augment(variable, domains, matching, MapSet.new()) do
+        {true, matching, _visited} -> {:cont, {:ok, matching}}
+        {false, _matching, _visited} -> {:halt, :error}
+      end
+    end)
+  end
+
+  # Directs the edges of the variables-values graph: from each variable to its matched value,
+  # and from each value to the other variables having it in their domains.
+  @spec residual_graph([Csp.variable()], %{Csp.variable() => Csp.domain()}, matching()) :: graph()
+  defp residual_graph(variables, domains, matching) do
+    Enum.reduce(variables, %{}, fn variable, graph ->
+      Map.fetch!(domains, variable)
+      |> Enum.reduce(graph, fn value, graph ->
+        if Map.get(matching, value) == variable do
+          Map.update(graph, {:variable, variable}, [{:value, value}], &[{:value, value} | &1])
+          |> Map.put_new({:value, value}, [])
+        else
+          Map.update(graph, {:value, value}, [{:variable, variable}], &[{:variable, variable} | &1])
+        end
+      end)
+    end)
+  end
+
+  # Finds strongly connected components of the graph reachable from `nodes` with Tarjan's algorithm,
+  # returning a map from each node to the root node of its component.
+  @spec strongly_connected_components([graph_node()], graph()) :: %{graph_node() => graph_node()}
+  defp strongly_connected_components(nodes, successors) do
+    state = %{index: 0, indices: %{}, lowlinks: %{}, stack: [], on_stack: MapSet.new(), components: %{}}
+
+    Enum.reduce(nodes, state, fn node, state ->
+      if Map.has_key?(state.indices, node), do: state, else: strong_connect(node, successors, state)
+    end).components
+  end
+
+  @spec strong_connect(graph_node(), graph(), map()) :: map()
+  defp strong_connect(node, successors, state) do
+    state = %{
+      state
+      | index: state.index + 1,
+        indices: Map.put(state.indices, node, state.index),
+        lowlinks: Map.put(state.lowlinks, node, state.index),
+        stack: [node | state.stack],
+        on_stack: MapSet.put(state.on_stack, node)
+    }
+
+    state =
+      Map.get(successors, node, [])
+      |> Enum.reduce(state, fn successor, state ->
+        cond do
+          !Map.has_key?(state.indices, successor) ->
+            state = strong_connect(successor, successors, state)
+            %{state | lowlinks: Map.update!(state.lowlinks, node, &min(&1, Map.fetch!(state.lowlinks, successor)))}
+
+          MapSet.member?(state.on_stack, successor) ->
+            %{state | lowlinks: Map.update!(state.lowlinks, node, &min(&1, Map.fetch!(state.indices, successor)))}
+
+          true ->
+            state
+        end
+      end)
+
+    if Map.fetch!(state.lowlinks, node) == Map.fetch!(state.indices, node) do
+      {component, [^node | stack]} = Enum.split_while(state.stack, &(&1 != node))
+      component = [node | component]
+
+      %{
+        state
+        | stack: stack,
+          on_stack: MapSet.difference(state.on_stack, MapSet.new(component)),
+          components: Enum.reduce(component, state.components, &Map.put(&2, &1, node))
+      }
+    else
+      state
+    end
+  end
+
+  # Returns the set of nodes reachable from `nodes`, including them.
+  @spec reachable([graph_node()], graph(), MapSet.t(graph_node())) :: MapSet.t(graph_node())
+  defp reachable(nodes, successors, visited) do
+    Enum.reduce(nodes, visited, fn node, visited ->
+      if MapSet.member?(visited, node) do
+        visited
+      else
+        reachable(Map.get(successors, node, []), successors, MapSet.put(visited, node))
+      end
+    end)
+  end
+
+  # Searches for an augmenting path starting from the unmatched `variable` (Kuhn's algorithm).
+  @spec augment(Csp.variable(), %{Csp.variable() => Csp.domain()}, matching(), MapSet.t(Csp.value())) ::
+          {found :: boolean(), matching(), MapSet.t(Csp.value())}
+  defp augment(variable, domains, matching, visited) do
+    Map.fetch!(domains, variable)
+    |> Enum.reduce_while({false, matching, visited}, fn value, {false, matching, visited} ->
+      if MapSet.member?(visited, value) do
+        {:cont, {false, matching, visited}}
+      else
+        visited = MapSet.put(visited, value)
+
+        case Map.fetch(matching, value) do
+          :error ->
+            {:halt, {true, Map.put(matching, value, variable), visited}}
+
+          {:ok, other_variable} ->
+            case augment(other_variable, domains, matching, visited) do
+              {true, matching, visited} -> {:halt, {true, Map.put(matching, value, variable), visited}}
+              {false, _matching, visited} -> {:cont, {false, matching, visited}}
+            end
+        end
+      end
+    end)
+  end
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.AllDifferent.t()) :: [Csp.variable()]
+    def arguments(%{variables: variables}), do: variables
+
+    @spec satisfies?(Csp.Constraints.AllDifferent.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{variables: variables}, assignment) do
+      values = Map.take(assignment, variables) |> Map.values()
+
+      length(Enum.uniq(values)) == length(values)
+    end
+  end
+end
@@ -0,0 +1,26 @@
+defmodule Csp.Constraints.And do
+  @moduledoc """
+  Conjunction of `constraints`: satisfied when all of them are satisfied.
+
+  Constraints with not yet assigned arguments are skipped, so the conjunction can also be checked
+  against partial assignments.
+  """
+  alias Csp.Constraint
+
+  @type t :: %__MODULE__{constraints: [Constraint.t()]}
+
+  defstruct [:constraints]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.And.t()) :: [Csp.variable()]
+    def arguments(%{constraints: constraints}), do: constraints |> Enum.flat_map(&Constraint.arguments/1) |> Enum.uniq()
+
+    @spec satisfies?(Csp.Constraints.And.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{constraints: constraints}, assignment) do
+      Enum.all?(constraints, fn constraint ->
+        !Enum.all?(Constraint.arguments(constraint), &Map.has_key?(assignment, &1)) or
+          Constraint.satisfies?(constraint, assignment)
+      end)
+    end
+  end
+end
@@ -0,0 +1,86 @@
+defmodule Csp.Constraints.Cardinality do
+  @moduledoc """
+  A cardinality constraint requiring the number of `variables` assigned to `value`
+  to be between `min` and `max` (inclusive).
+
+  See `Csp.Constraints.at_most/3`, `Csp.Constraints.at_least/3`, and `Csp.Constraints.exactly/3`
+  for convenient constructors.
+
+  For partial assignments, the constraint is considered violated once more than `max` of the assigned variables
+  have `value`, or once too few variables are left unassigned to reach `min`.
+
+  When searching with forward checking or AC-3, `Csp.Backtracking` narrows domains with `prune/2`,
+  so a count that can't be met is noticed right after the assignment that made it so.
+  """
+
+  @type t :: %__MODULE__{
+          variables: [Csp.variable()],
+          value: Csp.value(),
+          min: non_neg_integer(),
+          max: non_neg_integer() | :infinity
+        }
+
+  defstruct [:variables, :value, min: 0, max: :infinity]
+
+  @doc """
+  Enforces generalized arc consistency of the cardinality `constraint` on `domains`,
+  a map from each of the constraint's variables to its domain.
+
+  Once `max` variables can only take `value`, it's removed from the domains of the other variables;
+  once only `min` variables can take `value`, their domains are reduced to it.
+
+  Returns `{:ok, pruned_domains}`, or `:no_solution` if the count can't be met.
+  """
+  @spec prune(t(), %{Csp.variable() => Csp.domain()}) :: {:ok, %{Csp.variable() => Csp.domain()}} | :no_solution
+  def prune(%__MODULE__{variables: variables, value: value, min: min, max: max}, domains) do
+    fixed = Enum.count(variables, fn variable -> Map.fetch!(domains, variable) == [value] end)
+    possible = Enum.count(variables, fn variable -> value in Map.fetch!(domains, variable) end)
+
+    cond do
+      Enum.any?(domains, fn {_variable, domain} -> domain == [] end) ->
+        :no_solution
+
+      possible < min or (max != :infinity and fixed > max) ->
+        :no_solution
+
+      possible == min ->
+        pruned_domains =
+          Map.new(domains, fn {variable, domain} ->
+            if value in domain, do: {variable, [value]}, else: {variable, domain}
+          end)
+
+        {:ok, pruned_domains}
+
+      fixed == max ->
+        pruned_domains =
+          Map.new(domains, fn
+            {variable, [^value]} -> {variable, [value]}
+            {variable, domain} -> {variable, List.delete(domain, value)}
+          end)
+
+        {:ok, pruned_domains}
+
+      true ->
+        {:ok, domains}
+    end
+  end
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Cardinality.t()) :: [Csp.variable()]
+    def arguments(%{variables: variables}), do: variables
+
+    @spec satisfies?(Csp.Constraints.Cardinality.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{variables: variables, value: value, min: min, max: max}, assignment) do
+      {matching, unassigned} =
+        Enum.reduce(variables, {0, 0}, fn variable, {matching, unassigned} ->
+          case Map.fetch(assignment, variable) do
+            {:ok, ^value} -> {matching + 1, unassigned}
+            {:ok, _another_value} -> {matching, unassigned}
+            :error -> {matching, unassigned + 1}
+          end
+        end)
+
+      matching + unassigned >= min and (max == :infinity or matching <= max)
+    end
+  end
+end
@@ -0,0 +1,49 @@
+defmodule Csp.Constraints.Channeling do
+  @moduledoc """
+  A channeling constraint between two viewpoints of a permutation problem:
+  `x[i] == j` if and only if `y[j] == i`.
+
+  For example, in an assignment problem `x` could map workers to tasks, and `y` tasks to workers.
+  Modeling both viewpoints and linking them with this constraint lets constraints stated
+  on either viewpoint prune the search.
+
+  Indexes are 0-based positions in the `x` and `y` lists: `x` and `y` should have the same length `n`,
+  and domains of all their variables should be subsets of `0..n-1`. Values outside of that range
+  are considered violations. Pairs where one of the sides is not assigned yet are not checked.
+  """
+
+  @type t :: %__MODULE__{x: [Csp.variable()], y: [Csp.variable()]}
+
+  defstruct [:x, :y]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Channeling.t()) :: [Csp.variable()]
+    def arguments(%{x: x, y: y}), do: x ++ y
+
+    @spec satisfies?(Csp.Constraints.Channeling.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{x: x, y: y}, assignment) do
+      channeled?(x, y, assignment) and channeled?(y, x, assignment)
+    end
+
+    # Checks that for every assigned `from[i] == j`, `to[j]` is either unassigned or equal to `i`.
+    @spec channeled?([Csp.variable()], [Csp.variable()], Csp.assignment()) :: boolean()
+    defp channeled?(from, to, assignment) do
+      to = List.to_tuple(to)
+
+      from
+      |> Enum.with_index()
+      |> Enum.all?(fn {variable, index} ->
+        case Map.fetch(assignment, variable) do
+          {:ok, value} when is_integer(value) and value >= 0 and value < tuple_size(to) ->
+            Map.get(assignment, elem(to, value), index) == index
+
+          {:ok, _value} ->
+            false
+
+          :error ->
+            true
+        end
+      end)
+    end
+  end
+end
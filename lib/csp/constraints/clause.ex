@@ -0,0 +1,37 @@
+defmodule Csp.Constraints.Clause do
+  @moduledoc """
+  A propositional clause: a disjunction of `literals` over boolean variables.
+
+  Each literal is a `{variable, polarity}` tuple, and is matched when `variable` is assigned `polarity`.
+  The clause is satisfied when at least one of its literals is matched. For example,
+  this constraint encodes `x or not y or z`:
+
+  ```elixir
+  %Csp.Constraints.Clause{literals: [x: true, y: false, z: true]}
+  ```
+
+  Together with `[true, false]` domains, clauses allow modeling SAT problems (and boolean parts of larger CSPs).
+  While some of the clause's variables are not assigned yet, the clause is considered satisfied,
+  since an unassigned literal can still be matched.
+  """
+
+  @type literal :: {Csp.variable(), boolean()}
+  @type t :: %__MODULE__{literals: [literal()]}
+
+  defstruct [:literals]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Clause.t()) :: [Csp.variable()]
+    def arguments(%{literals: literals}), do: literals |> Enum.map(&elem(&1, 0)) |> Enum.uniq()
+
+    @spec satisfies?(Csp.Constraints.Clause.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{literals: literals}, assignment) do
+      Enum.any?(literals, fn {variable, polarity} ->
+        case Map.fetch(assignment, variable) do
+          {:ok, value} -> value == polarity
+          :error -> true
+        end
+      end)
+    end
+  end
+end
@@ -0,0 +1,52 @@
+defmodule Csp.Constraints.Cumulative do
+  @moduledoc """
+  A cumulative (resource) constraint for scheduling.
+
+  Each task `i` is described by the variable `Enum.at(starts, i)` holding its start time,
+  its fixed `Enum.at(durations, i)`, and its fixed `Enum.at(demands, i)` of the shared resource.
+  A task occupies the resource during times `start..start + duration - 1`. The constraint requires
+  the total demand of the tasks running at any time to not exceed `capacity`.
+
+  Start times should be integers, and durations and demands non-negative integers.
+  Tasks that have no start time assigned yet are ignored, so the constraint can also be checked
+  against partial assignments.
+  """
+
+  @type t :: %__MODULE__{
+          starts: [Csp.variable()],
+          durations: [non_neg_integer()],
+          demands: [non_neg_integer()],
+          capacity: non_neg_integer()
+        }
+
+  defstruct [:starts, :durations, :demands, :capacity]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Cumulative.t()) :: [Csp.variable()]
+    def arguments(%{starts: starts}), do: starts
+
+    @spec satisfies?(Csp.Constraints.Cumulative.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{starts: starts, durations: durations, demands: demands, capacity: capacity}, assignment) do
+      tasks =
+        Enum.zip([starts, durations, demands])
+        |> Enum.flat_map(fn {start, duration, demand} ->
+          case Map.fetch(assignment, start) do
+            {:ok, start_time} when duration > 0 -> [{start_time, start_time + duration, demand}]
+            _ -> []
+          end
+        end)
+
+      # the resource usage can only grow at start times, so it's enough to check it at those times
+      Enum.all?(tasks, fn {time, _end_time, _demand} ->
+        usage = for {start_time, end_time, demand} <- tasks, start_time <= time and time < end_time, do: demand
+        Enum.sum(usage) <= capacity
+      end)
+    end
+  end
+
+  defimpl Csp.ConstraintCost do
+    # the usage is summed over all tasks at each task's start time
+    @spec estimate(Csp.Constraints.Cumulative.t()) :: non_neg_integer()
+    def estimate(%{starts: starts}), do: length(starts) * length(starts)
+  end
+end
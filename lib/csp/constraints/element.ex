@@ -0,0 +1,44 @@
+defmodule Csp.Constraints.Element do
+  @moduledoc """
+  An element constraint: `result` is equal to the element of the `array` of variables at position `index`,
+  i.e., `result == Enum.at(array, index)`, where both `index` and `result` are variables.
+
+  Positions are 0-based, so the domain of `index` should be a subset of `0..length(array)-1`;
+  other values of `index` are considered violations.
+
+  For partial assignments, the constraint is considered violated only when the assigned values
+  already rule out every possible value of `index`.
+  """
+
+  @type t :: %__MODULE__{index: Csp.variable(), array: [Csp.variable()], result: Csp.variable()}
+
+  defstruct [:index, :array, :result]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Element.t()) :: [Csp.variable()]
+    def arguments(%{index: index, array: array, result: result}), do: Enum.uniq([index, result | array])
+
+    @spec satisfies?(Csp.Constraints.Element.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{index: index, array: array, result: result}, assignment) do
+      case Map.fetch(assignment, index) do
+        {:ok, index_value} when is_integer(index_value) and index_value >= 0 and index_value < length(array) ->
+          matches?(Enum.at(array, index_value), result, assignment)
+
+        {:ok, _index_value} ->
+          false
+
+        :error ->
+          Enum.any?(array, &matches?(&1, result, assignment))
+      end
+    end
+
+    # Checks if `variable` and `result` can still be equal.
+    @spec matches?(Csp.variable(), Csp.variable(), Csp.assignment()) :: boolean()
+    defp matches?(variable, result, assignment) do
+      case {Map.fetch(assignment, variable), Map.fetch(assignment, result)} do
+        {{:ok, value}, {:ok, result_value}} -> value == result_value
+        _ -> true
+      end
+    end
+  end
+end
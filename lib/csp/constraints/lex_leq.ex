@@ -0,0 +1,32 @@
+defmodule Csp.Constraints.LexLeq do
+  @moduledoc """
+  A constraint requiring the values of `left` variables to be lexicographically less than
+  or equal to the values of `right` variables. Commonly used to break symmetries
+  between interchangeable variables or groups of variables.
+
+  `left` and `right` should have the same length. For partial assignments, the constraint is
+  considered violated only if the assigned prefix already proves `left > right`.
+  """
+
+  @type t :: %__MODULE__{left: [Csp.variable()], right: [Csp.variable()]}
+
+  defstruct [:left, :right]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.LexLeq.t()) :: [Csp.variable()]
+    def arguments(%{left: left, right: right}), do: left ++ right
+
+    @spec satisfies?(Csp.Constraints.LexLeq.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{left: left, right: right}, assignment) do
+      Enum.zip(left, right)
+      |> Enum.reduce_while(true, fn {left_variable, right_variable}, true ->
+        case {Map.fetch(assignment, left_variable), Map.fetch(assignment, right_variable)} do
+          {{:ok, same}, {:ok, same}} -> {:cont, true}
+          {{:ok, left_value}, {:ok, right_value}} -> {:halt, left_value < right_value}
+          # the rest of the tuples is not decided yet
+          _ -> {:halt, true}
+        end
+      end)
+    end
+  end
+end
@@ -0,0 +1,94 @@
+defmodule Csp.Constraints.Linear do
+  @moduledoc """
+  A linear arithmetic constraint: `c1 * x1 + c2 * x2 + ... <op> rhs`,
+  where `op` is one of `:<`, `:<=`, `:==`, `:>=`, `:>`, `:!=`.
+
+  For example, `2x + 3y - z <= 7` is represented as:
+
+  ```elixir
+  %Csp.Constraints.Linear{terms: [x: 2, y: 3, z: -1], op: :<=, rhs: 7}
+  ```
+
+  Partial assignments that don't bind all of the `terms`' variables are considered satisfying.
+  Forward checking and AC-3 in `Csp.Backtracking` use `prune/2` to reason about the bounds of the sum instead,
+  rejecting a partial assignment as soon as no values left in the domains can satisfy the constraint.
+  """
+
+  @type op :: :< | :<= | :== | :>= | :> | :!=
+  @type t :: %__MODULE__{terms: [{Csp.variable(), number()}], op: op(), rhs: number()}
+
+  defstruct [:terms, :op, :rhs]
+
+  @doc """
+  Removes values that can't satisfy the linear `constraint` from `domains`,
+  a map from each of the constraint's variables to its (numeric) domain.
+
+  A value is kept if the constraint holds for some sum between the smallest and the largest sums
+  that the other terms can add to it, so pruning is sound, but not complete for `:==` and `:!=`.
+
+  Returns `{:ok, pruned_domains}`, or `:no_solution` if some domain becomes empty.
+  """
+  @spec prune(t(), %{Csp.variable() => Csp.domain()}) :: {:ok, %{Csp.variable() => Csp.domain()}} | :no_solution
+  def prune(%__MODULE__{terms: terms, op: op, rhs: rhs}, domains) do
+    coefficients =
+      Enum.reduce(terms, %{}, fn {variable, coefficient}, coefficients ->
+        Map.update(coefficients, variable, coefficient, &(&1 + coefficient))
+      end)
+
+    if Enum.any?(coefficients, fn {variable, _coefficient} -> Map.fetch!(domains, variable) == [] end) do
+      :no_solution
+    else
+      bounds =
+        Map.new(coefficients, fn {variable, coefficient} ->
+          {variable, Enum.min_max(Enum.map(Map.fetch!(domains, variable), &(coefficient * &1)))}
+        end)
+
+      {min, max} = Enum.reduce(bounds, {0, 0}, fn {_variable, {low, high}}, {min, max} -> {min + low, max + high} end)
+
+      pruned_domains =
+        Map.new(coefficients, fn {variable, coefficient} ->
+          {low, high} = Map.fetch!(bounds, variable)
+
+          domain =
+            Enum.filter(Map.fetch!(domains, variable), fn value ->
+              satisfiable?(op, rhs, min - low + coefficient * value, max - high + coefficient * value)
+            end)
+
+          {variable, domain}
+        end)
+
+      if Enum.any?(pruned_domains, fn {_variable, domain} -> domain == [] end) do
+        :no_solution
+      else
+        {:ok, pruned_domains}
+      end
+    end
+  end
+
+  ## Helpers
+
+  # Checks if `op rhs` holds for some sum between `min` and `max`.
+  @spec satisfiable?(op(), number(), number(), number()) :: boolean()
+  defp satisfiable?(:<, rhs, min, _max), do: min < rhs
+  defp satisfiable?(:<=, rhs, min, _max), do: min <= rhs
+  defp satisfiable?(:==, rhs, min, max), do: min <= rhs and rhs <= max
+  defp satisfiable?(:>=, rhs, _min, max), do: max >= rhs
+  defp satisfiable?(:>, rhs, _min, max), do: max > rhs
+  defp satisfiable?(:!=, rhs, min, max), do: min != max or min != rhs
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Linear.t()) :: [Csp.variable()]
+    def arguments(%{terms: terms}), do: Enum.map(terms, fn {variable, _coefficient} -> variable end)
+
+    @spec satisfies?(Csp.Constraints.Linear.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{terms: terms, op: op, rhs: rhs}, assignment) do
+      if Enum.all?(terms, fn {variable, _coefficient} -> Map.has_key?(assignment, variable) end) do
+        lhs = Enum.reduce(terms, 0, fn {variable, coefficient}, sum -> sum + coefficient * assignment[variable] end)
+
+        apply(Kernel, op, [lhs, rhs])
+      else
+        true
+      end
+    end
+  end
+end
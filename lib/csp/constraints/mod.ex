@@ -0,0 +1,25 @@
+defmodule Csp.Constraints.Mod do
+  @moduledoc """
+  A unary arithmetic constraint requiring the integer `variable` modulo `modulus` to be equal to `remainder`,
+  where modulo is computed with `Integer.mod/2` (i.e., the result has the same sign as `modulus`).
+
+  The constraint is considered satisfied while `variable` is not assigned yet.
+  """
+
+  @type t :: %__MODULE__{variable: Csp.variable(), modulus: integer(), remainder: integer()}
+
+  defstruct [:variable, :modulus, :remainder]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Mod.t()) :: [Csp.variable()]
+    def arguments(%{variable: variable}), do: [variable]
+
+    @spec satisfies?(Csp.Constraints.Mod.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{variable: variable, modulus: modulus, remainder: remainder}, assignment) do
+      case Map.fetch(assignment, variable) do
+        {:ok, value} -> Integer.mod(value, modulus) == remainder
+        :error -> true
+      end
+    end
+  end
+end
@@ -0,0 +1,34 @@
+defmodule Csp.Constraints.NValues do
+  @moduledoc """
+  A constraint on the number of distinct values taken by `variables`: `nvalues(variables) <op> count`,
+  where `op` is one of `:<`, `:<=`, `:==`, `:>=`, `:>`, `:!=`.
+
+  For example, this constraint requires the states to use at most 3 different colors:
+
+  ```elixir
+  %Csp.Constraints.NValues{variables: [:WA, :NT, :SA, :Q], op: :<=, count: 3}
+  ```
+
+  For partial assignments, the number of distinct values is known to be at least the number of distinct
+  assigned values, and at most that plus the number of unassigned variables. The constraint is considered
+  violated only if no number in that range satisfies `op`.
+  """
+
+  @type t :: %__MODULE__{variables: [Csp.variable()], op: Csp.Constraints.Linear.op(), count: non_neg_integer()}
+
+  defstruct [:variables, :op, :count]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.NValues.t()) :: [Csp.variable()]
+    def arguments(%{variables: variables}), do: variables
+
+    @spec satisfies?(Csp.Constraints.NValues.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{variables: variables, op: op, count: count}, assignment) do
+      assigned_values = Map.take(assignment, variables) |> Map.values()
+      distinct = assigned_values |> Enum.uniq() |> length()
+      unassigned = length(Enum.uniq(variables)) - length(assigned_values)
+
+      Enum.any?(distinct..(distinct + unassigned), fn nvalues -> apply(Kernel, op, [nvalues, count]) end)
+    end
+  end
+end
@@ -0,0 +1,25 @@
+defmodule Csp.Constraints.Not do
+  @moduledoc """
+  Negation of another `constraint`: satisfied when the wrapped constraint is not.
+
+  Negating a check of a partial assignment is unsound (an inner constraint that is not violated yet
+  can still be violated later), so the negation is only evaluated when all arguments
+  of the wrapped constraint are assigned. Until then, the constraint is considered satisfied.
+  """
+  alias Csp.Constraint
+
+  @type t :: %__MODULE__{constraint: Constraint.t()}
+
+  defstruct [:constraint]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Not.t()) :: [Csp.variable()]
+    def arguments(%{constraint: constraint}), do: Constraint.arguments(constraint)
+
+    @spec satisfies?(Csp.Constraints.Not.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{constraint: constraint}, assignment) do
+      !Enum.all?(Constraint.arguments(constraint), &Map.has_key?(assignment, &1)) or
+        !Constraint.satisfies?(constraint, assignment)
+    end
+  end
+end
@@ -0,0 +1,26 @@
+defmodule Csp.Constraints.Or do
+  @moduledoc """
+  Disjunction of `constraints`: satisfied when at least one of them is satisfied.
+
+  A constraint with not yet assigned arguments can still become satisfied, so the disjunction is only
+  considered violated when all of its constraints are fully assigned and violated.
+  """
+  alias Csp.Constraint
+
+  @type t :: %__MODULE__{constraints: [Constraint.t()]}
+
+  defstruct [:constraints]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Or.t()) :: [Csp.variable()]
+    def arguments(%{constraints: constraints}), do: constraints |> Enum.flat_map(&Constraint.arguments/1) |> Enum.uniq()
+
+    @spec satisfies?(Csp.Constraints.Or.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{constraints: constraints}, assignment) do
+      Enum.any?(constraints, fn constraint ->
+        !Enum.all?(Constraint.arguments(constraint), &Map.has_key?(assignment, &1)) or
+          Constraint.satisfies?(constraint, assignment)
+      end)
+    end
+  end
+end
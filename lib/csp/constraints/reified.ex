@@ -0,0 +1,38 @@
+defmodule Csp.Constraints.Reified do
+  @moduledoc """
+  Reification of a `constraint`: the `indicator` variable, with a domain of `0` and `1`,
+  is equal to `1` exactly when `constraint` is satisfied.
+
+  For example, this constraint makes `:b` equal to `1` if and only if `x < y`:
+
+  ```elixir
+  %Csp.Constraints.Reified{indicator: :b, constraint: {[:x, :y], fn [x, y] -> x < y end}}
+  ```
+
+  The constraint is only checked when both `indicator` and all arguments of the reified `constraint`
+  are assigned; until then, either side can still be adjusted to match the other.
+  Other constraints can then use `indicator`, e.g., to count how many constraints hold.
+  """
+  alias Csp.Constraint
+
+  @type t :: %__MODULE__{indicator: Csp.variable(), constraint: Constraint.t()}
+
+  defstruct [:indicator, :constraint]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Reified.t()) :: [Csp.variable()]
+    def arguments(%{indicator: indicator, constraint: constraint}) do
+      Enum.uniq([indicator | Constraint.arguments(constraint)])
+    end
+
+    @spec satisfies?(Csp.Constraints.Reified.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{indicator: indicator, constraint: constraint}, assignment) do
+      with {:ok, indicator_value} <- Map.fetch(assignment, indicator),
+           true <- Enum.all?(Constraint.arguments(constraint), &Map.has_key?(assignment, &1)) do
+        indicator_value == if(Constraint.satisfies?(constraint, assignment), do: 1, else: 0)
+      else
+        _ -> true
+      end
+    end
+  end
+end
@@ -0,0 +1,45 @@
+defmodule Csp.Constraints.Table do
+  @moduledoc """
+  An extensional (table) constraint, listing all allowed combinations of values of its `variables`.
+
+  Each element of `tuples` is a list of values, in the same order as `variables`.
+  For example, this constraint allows only three pairs of values for `:shirt` and `:pants`:
+
+  ```elixir
+  %Csp.Constraints.Table{
+    variables: [:shirt, :pants],
+    tuples: [[:white, :black], [:white, :blue], [:blue, :white]]
+  }
+  ```
+
+  Variables that are not assigned yet match any value, so the constraint can also be checked
+  against partial assignments.
+  """
+
+  @type t :: %__MODULE__{variables: [Csp.variable()], tuples: [[Csp.value()]]}
+
+  defstruct [:variables, :tuples]
+
+  defimpl Csp.Constraint do
+    @spec arguments(Csp.Constraints.Table.t()) :: [Csp.variable()]
+    def arguments(%{variables: variables}), do: variables
+
+    @spec satisfies?(Csp.Constraints.Table.t(), Csp.assignment()) :: boolean()
+    def satisfies?(%{variables: variables, tuples: tuples}, assignment) do
+      Enum.any?(tuples, fn tuple ->
+        Enum.zip(variables, tuple)
+        |> Enum.all?(fn {variable, value} ->
+          case Map.fetch(assignment, variable) do
+            {:ok, assigned_value} -> assigned_value == value
+            :error -> true
+          end
+        end)
+      end)
+    end
+  end
+
+  defimpl Csp.ConstraintCost do
+    @spec estimate(Csp.Constraints.Table.t()) :: non_neg_integer()
+    def estimate(%{variables: variables, tuples: tuples}), do: length(variables) * length(tuples)
+  end
+end
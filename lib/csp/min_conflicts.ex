@@ -3,6 +3,7 @@ defmodule Csp.MinConflicts do
   [Min-conflicts](https://en.wikipedia.org/wiki/Min-conflicts_algorithm) algorithm implementation,
   with Tabu search to allow overcoming local minimums.
   """
+  alias Csp.Random
 
   @doc """
   Solves `csp` with min-conflicts algorithm, using tabu search to overcome local minimums.
@@ -11,12 +12,15 @@ defmodule Csp.MinConflicts do
 
   Supported `opts`:
 
-    - `:max_iteration` - positive integer, the number of iteration to perform before giving up.
+    - `:max_iterations` - positive integer, the number of iterations to perform before giving up.
     Defaults to `10_000`.
     - `:optimize_initial_state` - boolean, defaults to `false`. If set to `true`, will use a greedy
     algorithm to set an initial state minimizing the number of conflicts for each variable.
     - `:tabu_depth` - positive integer or `nil`, defaults to `nil`. If set to an integer,
     will limit tabu stack depth by the specified integer.
+    - `:seed` - integer or `nil`, defaults to `nil`. The initial state and the moves picked on each iteration
+    are random; with an integer `seed` they are reproducible, so the same `csp` and `opts` always give
+    the same result. If `nil`, every run is different. The calling process' random number generator isn't used.
   """
   @spec solve(Csp.t(), Keyword.t()) :: {:solved, Csp.assignment()} | :no_solution
   def solve(csp, opts \\ []) do
@@ -24,17 +28,20 @@ defmodule Csp.MinConflicts do
     optimize_initial_state = Keyword.get(opts, :optimize_initial_state, false)
     tabu_depth = Keyword.get(opts, :tabu_depth)
 
-    assignment = if optimize_initial_state, do: optimized_initial_state(csp), else: random_initial_state(csp)
+    rand = Random.new(Keyword.get(opts, :seed))
 
-    {status, assignment, _tabu} =
+    {assignment, rand} =
+      if optimize_initial_state, do: optimized_initial_state(csp, rand), else: random_initial_state(csp, rand)
+
+    {status, assignment, _tabu, _rand} =
       1..max_iterations
-      |> Enum.reduce_while({:no_solution, assignment, []}, fn _iteration, {status, assignment, tabu} ->
+      |> Enum.reduce_while({:no_solution, assignment, [], rand}, fn _iteration, {status, assignment, tabu, rand} ->
         # TODO: replace with Csp.solved?, since it's cheaper, and we always have full assignment here.
         if Csp.consistent?(csp, assignment) do
-          {:halt, {:solved, assignment, tabu}}
+          {:halt, {:solved, assignment, tabu, rand}}
         else
-          variable = Csp.conflicted(csp, assignment) |> Enum.random()
-          random_value = Enum.random(csp.domains[variable])
+          {variable, rand} = Csp.conflicted(csp, assignment) |> Random.element(rand)
+          {random_value, rand} = Random.element(csp.domains[variable], rand)
 
           # TODO: Make tabu a MapSet?
           # TODO: replace find with manual reduce_while to take the first value that is not in tabu, or generate a default value if it's not found
@@ -53,7 +60,7 @@ defmodule Csp.MinConflicts do
               tabu
             end
 
-          {:cont, {status, Map.put(assignment, variable, value), tabu}}
+          {:cont, {status, Map.put(assignment, variable, value), tabu, rand}}
         end
       end)
 
@@ -62,26 +69,26 @@ defmodule Csp.MinConflicts do
 
   ## Helpers
 
-  @spec random_initial_state(Csp.t()) :: Csp.assignment()
-  defp random_initial_state(%Csp{} = csp) do
-    Enum.reduce(csp.domains, %{}, fn {variable, values}, assignment ->
-      value = Enum.random(values)
-      Map.put(assignment, variable, value)
+  @spec random_initial_state(Csp.t(), :rand.state()) :: {Csp.assignment(), :rand.state()}
+  defp random_initial_state(%Csp{} = csp, rand) do
+    Enum.reduce(csp.domains, {%{}, rand}, fn {variable, values}, {assignment, rand} ->
+      {value, rand} = Random.element(values, rand)
+      {Map.put(assignment, variable, value), rand}
     end)
   end
 
-  @spec optimized_initial_state(Csp.t()) :: Csp.assignment()
-  defp optimized_initial_state(%Csp{} = csp) do
-    Enum.reduce(csp.variables, %{}, fn variable, assignment ->
-      value =
+  @spec optimized_initial_state(Csp.t(), :rand.state()) :: {Csp.assignment(), :rand.state()}
+  defp optimized_initial_state(%Csp{} = csp, rand) do
+    Enum.reduce(csp.variables, {%{}, rand}, fn variable, {assignment, rand} ->
+      {value, rand} =
         try do
-          Csp.min_conflicts_value!(csp, variable, assignment)
+          {Csp.min_conflicts_value!(csp, variable, assignment), rand}
         rescue
           KeyError ->
-            Map.fetch!(csp.domains, variable) |> Enum.random()
+            Map.fetch!(csp.domains, variable) |> Random.element(rand)
         end
 
-      Map.put(assignment, variable, value)
+      {Map.put(assignment, variable, value), rand}
     end)
   end
 end
@@ -0,0 +1,165 @@
+defmodule Csp.Optimization do
+  @moduledoc """
+  Branch-and-bound search for the best solution of a CSP according to some objective,
+  and for the best assignments of over-constrained CSPs (MAX-CSP).
+  """
+  alias Csp.Constraint
+
+  @type goal :: :minimize | :maximize
+  @type objective :: (Csp.assignment() -> number())
+  @type optimize_result :: {:solved, Csp.assignment(), objective_value :: number()} | :no_solution
+  @type weight :: non_neg_integer() | :infinity
+
+  @doc """
+  Finds a solution of `csp` with the best value of the `objective` function,
+  which accepts a complete assignment and returns a number.
+
+  Returns `{:solved, assignment, objective_value}`, or `:no_solution` if `csp` has no solutions.
+  If several solutions have the best objective value, the first one found is returned.
+
+  ## Options
+
+  The following `opts` are supported:
+
+  - `goal`, either `:minimize` (default) or `:maximize`.
+  - `bound`, a function accepting a partial assignment and returning an optimistic estimate
+  of the objective value of any solution extending it (a lower bound when minimizing,
+  and an upper bound when maximizing). When a branch's bound cannot beat the best solution found so far,
+  the branch is pruned. Without `bound`, every solution of `csp` is examined.
+  """
+  @spec solve(Csp.t(), objective(), Keyword.t()) :: optimize_result()
+  def solve(%Csp{} = csp, objective, opts \\ []) do
+    better? = if Keyword.get(opts, :goal, :minimize) == :minimize, do: &</2, else: &>/2
+    settings = %{objective: objective, bound: Keyword.get(opts, :bound), better?: better?}
+
+    case branch(%{}, csp.variables, csp, settings, nil) do
+      nil -> :no_solution
+      {assignment, objective_value} -> {:solved, assignment, objective_value}
+    end
+  end
+
+  @doc """
+  Finds an assignment of `csp` minimizing the total weight of violated constraints (MAX-CSP).
+
+  Useful for over-constrained problems, where `solve/2` would just return `:no_solution`.
+
+  Returns `{:solved, assignment, violated_weight}`, where `violated_weight` is the sum of weights
+  of constraints `assignment` violates, or `:no_solution` if the hard constraints can't be satisfied together.
+
+  ## Options
+
+  The following `opts` are supported:
+
+  - `weights`, a list of weights of `csp.constraints`, in the same order. Each weight is either
+  a non-negative integer (a soft constraint), or `:infinity` (a hard constraint that must be satisfied).
+  Defaults to weight `1` for every constraint, i.e., minimizing the number of violated constraints.
+  Raises `ArgumentError` if the number of weights differs from the number of constraints.
+  """
+  @spec solve_max_csp(Csp.t(), Keyword.t()) :: optimize_result()
+  def solve_max_csp(%Csp{} = csp, opts \\ []) do
+    weights = Keyword.get(opts, :weights, List.duplicate(1, length(csp.constraints)))
+
+    if length(weights) != length(csp.constraints) do
+      raise ArgumentError,
+            "expected #{length(csp.constraints)} constraint weights, got #{length(weights)}: #{inspect(weights)}"
+    end
+
+    weighted_constraints_index =
+      Enum.zip(csp.constraints, weights)
+      |> Enum.reverse()
+      |> Enum.reduce(%{}, fn {constraint, _weight} = weighted_constraint, index ->
+        Constraint.arguments(constraint)
+        |> Enum.uniq()
+        |> Enum.reduce(index, fn variable, index ->
+          Map.update(index, variable, [weighted_constraint], &[weighted_constraint | &1])
+        end)
+      end)
+
+    case branch_max_csp(%{}, csp.variables, 0, csp, weighted_constraints_index, nil) do
+      nil -> :no_solution
+      {assignment, violated_weight} -> {:solved, assignment, violated_weight}
+    end
+  end
+
+  ## Helpers
+
+  @typep incumbent :: {Csp.assignment(), number()} | nil
+
+  @spec branch(Csp.assignment(), [Csp.variable()], Csp.t(), map(), incumbent()) :: incumbent()
+  defp branch(assignment, unassigned, csp, settings, incumbent)
+
+  defp branch(assignment, [], _csp, settings, incumbent) do
+    objective_value = settings.objective.(assignment)
+
+    case incumbent do
+      nil -> {assignment, objective_value}
+      {_, best} -> if settings.better?.(objective_value, best), do: {assignment, objective_value}, else: incumbent
+    end
+  end
+
+  defp branch(assignment, [variable | unassigned], csp, settings, incumbent) do
+    Map.fetch!(csp.domains, variable)
+    |> Enum.reduce(incumbent, fn value, incumbent ->
+      assignment = Map.put(assignment, variable, value)
+
+      cond do
+        !Csp.consistent?(csp, assignment) -> incumbent
+        pruned?(assignment, settings, incumbent) -> incumbent
+        true -> branch(assignment, unassigned, csp, settings, incumbent)
+      end
+    end)
+  end
+
+  @spec pruned?(Csp.assignment(), map(), incumbent()) :: boolean()
+  defp pruned?(_assignment, %{bound: nil}, _incumbent), do: false
+  defp pruned?(_assignment, _settings, nil), do: false
+  defp pruned?(assignment, settings, {_, best}), do: !settings.better?.(settings.bound.(assignment), best)
+
+  @typep weighted_constraints_index :: %{Csp.variable() => [{Constraint.t(), weight()}]}
+  @typep max_csp_incumbent :: {Csp.assignment(), non_neg_integer()} | nil
+
+  @spec branch_max_csp(
+          Csp.assignment(),
+          [Csp.variable()],
+          non_neg_integer(),
+          Csp.t(),
+          weighted_constraints_index(),
+          max_csp_incumbent()
+        ) :: max_csp_incumbent()
+  defp branch_max_csp(assignment, unassigned, violated_weight, csp, index, incumbent)
+
+  defp branch_max_csp(assignment, [], violated_weight, _csp, _index, _incumbent) do
+    # branches that can't improve on the incumbent are pruned before reaching here
+    {assignment, violated_weight}
+  end
+
+  defp branch_max_csp(assignment, [variable | unassigned], violated_weight, csp, index, incumbent) do
+    Map.fetch!(csp.domains, variable)
+    |> Enum.reduce(incumbent, fn value, incumbent ->
+      assignment = Map.put(assignment, variable, value)
+
+      # each constraint is checked once, when the last of its arguments gets assigned
+      added_weight =
+        Map.get(index, variable, [])
+        |> Enum.reduce_while(0, fn {constraint, weight}, added_weight ->
+          cond do
+            !Enum.all?(Constraint.arguments(constraint), &Map.has_key?(assignment, &1)) -> {:cont, added_weight}
+            Constraint.satisfies?(constraint, assignment) -> {:cont, added_weight}
+            weight == :infinity -> {:halt, :infinity}
+            true -> {:cont, added_weight + weight}
+          end
+        end)
+
+      cond do
+        added_weight == :infinity ->
+          incumbent
+
+        incumbent != nil and violated_weight + added_weight >= elem(incumbent, 1) ->
+          incumbent
+
+        true ->
+          branch_max_csp(assignment, unassigned, violated_weight + added_weight, csp, index, incumbent)
+      end
+    end)
+  end
+end
@@ -0,0 +1,143 @@
+defmodule Csp.Parser do
+  @moduledoc """
+  Parser for a simple line-based CSP definition format.
+
+  Each line is one of:
+
+  - `var x 0..10` - defines variable `x` with an inclusive, ascending integer range domain
+  - `var x 2,3,5,7` - defines variable `x` with a domain of listed integers
+  - `eq x 5` / `neq x 5` - constrains `x` to be equal / not equal to the integer `5`
+  - `eq x y` / `neq x y` - constrains variables `x` and `y` to be equal / not equal
+  - `alldiff x y z` - constrains all listed variables to be different (see `Csp.Constraints.AllDifferent`)
+  - `linear x:2 y:3 z:-1 <= 7` - constrains `2x + 3y - z` to be less than or equal to `7`;
+  supported relations are `<`, `<=`, `==`, `>=`, `>`, and `!=` (see `Csp.Constraints.Linear`)
+  - `solve` - an optional final line marking the end of the definition; it has no effect on the problem
+
+  Empty lines and lines starting with `#` are ignored. Variable names are converted to atoms.
+
+  For example, this defines a problem of finding two different digits, one of them not equal to 5:
+
+  ```
+  var x 0..9
+  var y 0..9
+  neq x y
+  neq x 5
+  solve
+  ```
+  """
+  alias Csp.Constraints
+
+  @linear_ops ~w(< <= == >= > !=)
+
+  @doc """
+  Parses `input` into a `Csp` struct.
+
+  Returns `{:ok, csp}`, or `{:error, message}` if `input` is malformed.
+  Messages about malformed lines start with the (1-indexed) line number.
+  """
+  @spec parse(String.t()) :: {:ok, Csp.t()} | {:error, String.t()}
+  def parse(input) do
+    input
+    |> String.split("\n")
+    |> Enum.map(&String.trim/1)
+    |> Enum.with_index(1)
+    |> Enum.reject(fn {line, _line_number} -> line == "" or String.starts_with?(line, "#") end)
+    |> Enum.reduce_while({:ok, %Csp{}}, fn {line, line_number}, {:ok, csp} ->
+      case parse_line(String.split(line), csp) do
+        {:ok, csp} -> {:cont, {:ok, csp}}
+        {:error, message} -> {:halt, {:error, "line #{line_number}: #{message}"}}
+        :error -> {:halt, {:error, "line #{line_number}: unexpected line: #{line}"}}
+      end
+    end)
+    |> case do
+      {:ok, csp} ->
+        case Csp.build(csp) do
+          {:ok, csp} -> {:ok, csp}
+          {:error, reason} -> {:error, "invalid CSP: #{inspect(reason)}"}
+        end
+
+      {:error, _message} = error ->
+        error
+    end
+  end
+
+  ## Helpers
+
+  @spec parse_line([String.t()], Csp.t()) :: {:ok, Csp.t()} | {:error, String.t()} | :error
+  defp parse_line(tokens, csp)
+
+  defp parse_line(["solve"], csp), do: {:ok, csp}
+
+  defp parse_line(["var", variable, domain], csp) do
+    case Regex.run(~r/^(-?\d+)\.\.(-?\d+)$/, domain) do
+      [_, from, to] ->
+        {from, to} = {String.to_integer(from), String.to_integer(to)}
+
+        if from <= to do
+          {:ok, Csp.add_variable(csp, String.to_atom(variable), Enum.to_list(from..to))}
+        else
+          {:error, "empty or descending range #{domain} for variable #{variable}"}
+        end
+
+      nil ->
+        case parse_integers(String.split(domain, ",")) do
+          {:ok, domain} -> {:ok, Csp.add_variable(csp, String.to_atom(variable), domain)}
+          :error -> :error
+        end
+    end
+  end
+
+  defp parse_line([relation, variable, operand], csp) when relation in ["eq", "neq"] do
+    test = if relation == "eq", do: &==/2, else: &!=/2
+    variable = String.to_atom(variable)
+
+    constraint =
+      case Integer.parse(operand) do
+        {value, ""} -> {[variable], fn [x] -> test.(x, value) end}
+        _ -> {[variable, String.to_atom(operand)], fn [x, y] -> test.(x, y) end}
+      end
+
+    {:ok, Csp.add_constraint(csp, constraint)}
+  end
+
+  defp parse_line(["alldiff" | [_ | _] = variables], csp) do
+    {:ok, Csp.add_constraint(csp, Constraints.all_different(Enum.map(variables, &String.to_atom/1)))}
+  end
+
+  defp parse_line(["linear" | tokens], csp) when length(tokens) >= 3 do
+    {terms, [op, rhs]} = Enum.split(tokens, -2)
+
+    parsed_terms =
+      Enum.map(terms, fn term ->
+        with [variable, coefficient] <- String.split(term, ":"),
+             {coefficient, ""} <- Integer.parse(coefficient) do
+          {String.to_atom(variable), coefficient}
+        else
+          _ -> :error
+        end
+      end)
+
+    with false <- :error in parsed_terms,
+         true <- op in @linear_ops,
+         {rhs, ""} <- Integer.parse(rhs) do
+      {:ok, Csp.add_constraint(csp, Constraints.linear(parsed_terms, String.to_atom(op), rhs))}
+    else
+      _ -> :error
+    end
+  end
+
+  defp parse_line(_tokens, _csp), do: :error
+
+  @spec parse_integers([String.t()]) :: {:ok, [integer()]} | :error
+  defp parse_integers(strings) do
+    integers =
+      Enum.map(strings, fn string ->
+        case Integer.parse(string) do
+          {integer, ""} -> integer
+          _ -> :error
+        end
+      end)
+
+    if :error in integers, do: :error, else: {:ok, integers}
+  end
+end
@@ -5,6 +5,7 @@ defmodule Csp.Problems do
   import Csp.Domains
   import Csp.Constraints
 
+  alias Csp.Random
   alias IO.ANSI
 
   ################################
@@ -199,6 +200,34 @@ defmodule Csp.Problems do
     %{WA: :green, NT: :red, Q: :red, NSW: :green, V: :red, SA: :blue, T: :red}
   end
 
+  @doc """
+  Returns a [graph coloring](https://en.wikipedia.org/wiki/Graph_coloring) problem for the graph
+  with `vertices` and `edges` (a list of `{vertex1, vertex2}` tuples), with colors `1..colors`.
+
+  Each vertex is a variable, and adjacent vertices must have different colors. For example,
+  this colors the Australian states map with 3 colors:
+
+  ```elixir
+  alias Csp.Problems
+
+  states = ~w(WA NT Q NSW V SA T)a
+  borders = [WA: :NT, WA: :SA, NT: :SA, NT: :Q, SA: :Q, SA: :NSW, SA: :V, Q: :NSW, NSW: :V]
+  {:solved, coloring} = Csp.solve(Problems.graph_coloring(states, borders, 3))
+  ```
+
+  If the graph can't be colored with `colors` colors, solving the returned CSP gives `:no_solution`.
+  """
+  @spec graph_coloring([Csp.variable()], [{Csp.variable(), Csp.variable()}], pos_integer()) :: Csp.t()
+  def graph_coloring(vertices, edges, colors) when is_integer(colors) and colors > 0 do
+    domain = Enum.to_list(1..colors)
+
+    %Csp{
+      variables: vertices,
+      domains: Map.new(vertices, fn vertex -> {vertex, domain} end),
+      constraints: Enum.map(edges, fn {vertex1, vertex2} -> not_equal(vertex1, vertex2) end)
+    }
+  end
+
   ################################
   ## N Queens
   ################################
@@ -213,10 +242,11 @@ defmodule Csp.Problems do
   To solve this, we suggest running the following:
 
   ```elixir
-  alias Csp.{Problems, Searcher}
+  alias Csp.Problems
 
-  queens8 = nqueens()
-  Searcher.backtrack(queens8)
+  queens8 = Problems.nqueens()
+  {:solved, solution} = Csp.solve(queens8)
+  Problems.pretty_print_nqueens(solution)
   ```
   """
   @spec nqueens(non_neg_integer()) :: Csp.t()
@@ -271,10 +301,11 @@ defmodule Csp.Problems do
   To solve this, we suggest running the following:
 
   ```elixir
-  alias Csp.{Problems, Searcher}
+  alias Csp.Problems
 
-  queens8 = nqueens_slow()
-  Searcher.backtrack(queens8)
+  queens8 = Problems.nqueens_slow()
+  {:solved, solution} = Csp.solve(queens8)
+  Problems.pretty_print_nqueens_slow(solution)
   ```
   """
   @spec nqueens_slow(non_neg_integer()) :: Csp.t()
@@ -389,4 +420,50 @@ defmodule Csp.Problems do
 
     :ok
   end
+
+  ################################
+  ## Random binary CSPs
+  ################################
+
+  @doc """
+  Generates a random binary CSP with the [model B](https://doi.org/10.1023/A:1009706407756) generator.
+
+  The generated CSP has `n` variables (`1..n`), each with the domain `1..domain_size`.
+  Exactly `round(density * n * (n - 1) / 2)` distinct pairs of variables are constrained,
+  and each constraint forbids exactly `round(tightness * domain_size * domain_size)` pairs of values.
+  Both `density` and `tightness` should be between `0` and `1`.
+
+  The constrained pairs and their forbidden values are picked with a random number generator seeded with `seed`,
+  which is independent from the one of the calling process. Calls with the same arguments produce the same CSPs,
+  making them suitable for reproducible stress tests and benchmarks across the phase transition of satisfiability.
+  """
+  @spec random_binary(pos_integer(), pos_integer(), float(), float(), integer()) :: Csp.t()
+  def random_binary(n, domain_size, density, tightness, seed)
+      when n > 0 and domain_size > 0 and density >= 0 and density <= 1 and tightness >= 0 and tightness <= 1 do
+    rand = Random.new(seed)
+
+    variables = Enum.to_list(1..n)
+    domain = Enum.to_list(1..domain_size)
+
+    variable_pairs = for x <- variables, y <- variables, x < y, do: {x, y}
+    value_pairs = for x_value <- domain, y_value <- domain, do: {x_value, y_value}
+
+    {constrained_pairs, rand} = Random.take(variable_pairs, round(density * length(variable_pairs)), rand)
+
+    {constraints, _rand} =
+      constrained_pairs
+      |> Enum.sort()
+      |> Enum.map_reduce(rand, fn {x, y}, rand ->
+        {forbidden, rand} = Random.take(value_pairs, round(tightness * length(value_pairs)), rand)
+        forbidden = MapSet.new(forbidden)
+
+        {{[x, y], fn [x_value, y_value] -> !MapSet.member?(forbidden, {x_value, y_value}) end}, rand}
+      end)
+
+    %Csp{
+      variables: variables,
+      domains: Map.new(variables, fn variable -> {variable, domain} end),
+      constraints: constraints
+    }
+  end
 end
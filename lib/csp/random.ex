@@ -0,0 +1,44 @@
+defmodule Csp.Random do
+  # Random choices for randomized solvers and generators. The `:rand` state is passed around explicitly
+  # instead of being kept in the process dictionary, so the callers' random number generators are left untouched.
+  @moduledoc false
+
+  @doc """
+  Returns a new random number generator state, seeded with `seed`, or randomly if `seed` is `nil`.
+  """
+  @spec new(integer() | nil) :: :rand.state()
+  def new(nil), do: :rand.seed_s(:exsss)
+  def new(seed) when is_integer(seed), do: :rand.seed_s(:exsss, {seed, 0, 0})
+
+  @doc """
+  Returns a random element of a non-empty `list`.
+  """
+  @spec element(nonempty_list(), :rand.state()) :: {any(), :rand.state()}
+  def element(list, state) do
+    {index, state} = :rand.uniform_s(length(list), state)
+    {Enum.at(list, index - 1), state}
+  end
+
+  @doc """
+  Returns `list` shuffled.
+  """
+  @spec shuffle(list(), :rand.state()) :: {list(), :rand.state()}
+  def shuffle(list, state) do
+    {keyed, state} =
+      Enum.map_reduce(list, state, fn element, state ->
+        {key, state} = :rand.uniform_s(state)
+        {{key, element}, state}
+      end)
+
+    {keyed |> Enum.sort_by(fn {key, _element} -> key end) |> Enum.map(fn {_key, element} -> element end), state}
+  end
+
+  @doc """
+  Returns `count` random elements of `list`, in random order.
+  """
+  @spec take(list(), non_neg_integer(), :rand.state()) :: {list(), :rand.state()}
+  def take(list, count, state) do
+    {shuffled, state} = shuffle(list, state)
+    {Enum.take(shuffled, count), state}
+  end
+end
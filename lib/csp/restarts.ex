@@ -0,0 +1,94 @@
+defmodule Csp.Restarts do
+  @moduledoc """
+  Backtracking with randomized value ordering and restarts.
+
+  Runtimes of randomized backtracking are often heavy-tailed: most runs finish quickly,
+  but some get stuck in a fruitless part of the search space. Restarting the search
+  after a cutoff, with a fresh random value ordering, avoids waiting for those unlucky runs.
+  Cutoffs follow the [Luby sequence](https://doi.org/10.1016/0020-0190(93)90029-9).
+  """
+  alias Csp.Random
+
+  @doc """
+  Solves `csp` with randomized backtracking, restarting the search when it visits more nodes than
+  the current cutoff allows. The cutoff for `i`-th run is `luby(i) * luby_unit` nodes.
+
+  Returns `{:solved, assignment}`, `:no_solution` if a run explored the whole search space, proving that `csp`
+  has no solution, or `:restarts_exhausted` if all `max_restarts` runs were cut off before finding a solution.
+
+  ## Options
+
+  Supported `opts`:
+
+    - `:max_restarts` - positive integer, the number of runs to perform before giving up. Defaults to `100`.
+    - `:luby_unit` - positive integer, the number of nodes the Luby sequence is multiplied by. Defaults to `100`.
+    - `:seed` - integer or `nil`, defaults to `nil`. Seeds the value orderings of all runs: the same `seed`
+    gives the same sequence of runs, and so the same result. If `nil`, the orderings are seeded randomly.
+    Either way, the state of the calling process' random number generator is left as it was.
+  """
+  @spec solve(Csp.t(), Keyword.t()) :: {:solved, Csp.assignment()} | :no_solution | :restarts_exhausted
+  def solve(%Csp{} = csp, opts \\ []) do
+    max_restarts = Keyword.get(opts, :max_restarts, 100)
+    luby_unit = Keyword.get(opts, :luby_unit, 100)
+
+    rand = Random.new(Keyword.get(opts, :seed))
+
+    Enum.reduce_while(1..max_restarts, {:cutoff, rand}, fn run, {:cutoff, rand} ->
+      case search(%{}, csp.variables, csp, luby(run) * luby_unit, rand) do
+        {{:solved, _assignment} = solved, _nodes_left, _rand} -> {:halt, solved}
+        {:no_solution, _nodes_left, _rand} -> {:halt, :no_solution}
+        {:cutoff, _nodes_left, rand} -> {:cont, {:cutoff, rand}}
+      end
+    end)
+    |> case do
+      {:cutoff, _rand} -> :restarts_exhausted
+      result -> result
+    end
+  end
+
+  @doc """
+  Returns `i`-th (1-indexed) element of the Luby sequence: 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8, ...
+  """
+  @spec luby(pos_integer()) :: pos_integer()
+  def luby(i) when is_integer(i) and i > 0 do
+    k = Enum.find(1..64, fn k -> :erlang.bsl(1, k) - 1 >= i end)
+
+    if i == :erlang.bsl(1, k) - 1 do
+      :erlang.bsl(1, k - 1)
+    else
+      luby(i - :erlang.bsl(1, k - 1) + 1)
+    end
+  end
+
+  ## Helpers
+
+  @typep search_result ::
+           {{:solved, Csp.assignment()} | :no_solution | :cutoff, nodes_left :: non_neg_integer(), :rand.state()}
+
+  @spec search(Csp.assignment(), [Csp.variable()], Csp.t(), non_neg_integer(), :rand.state()) :: search_result()
+  defp search(assignment, unassigned, csp, nodes_left, rand)
+
+  defp search(assignment, [], _csp, nodes_left, rand), do: {{:solved, assignment}, nodes_left, rand}
+
+  defp search(assignment, [variable | unassigned], csp, nodes_left, rand) do
+    {values, rand} = Random.shuffle(Map.fetch!(csp.domains, variable), rand)
+
+    Enum.reduce_while(values, {:no_solution, nodes_left, rand}, fn value, {:no_solution, nodes_left, rand} ->
+      assignment = Map.put(assignment, variable, value)
+
+      cond do
+        nodes_left == 0 ->
+          {:halt, {:cutoff, 0, rand}}
+
+        Csp.consistent?(csp, assignment) ->
+          case search(assignment, unassigned, csp, nodes_left - 1, rand) do
+            {:no_solution, nodes_left, rand} -> {:cont, {:no_solution, nodes_left, rand}}
+            solved_or_cutoff -> {:halt, solved_or_cutoff}
+          end
+
+        true ->
+          {:cont, {:no_solution, nodes_left - 1, rand}}
+      end
+    end)
+  end
+end
@@ -43,4 +43,38 @@ defmodule Csp.AC3Test do
     assert reduced_csp.variables == csp.variables
     assert reduced_csp.constraints == csp.constraints
   end
+
+  test "reduce reports domain wipeouts" do
+    x_below_y = {[:x, :y], fn [x, y] -> x < y end}
+
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [2, 3], y: [1, 2]},
+      constraints: [x_below_y]
+    }
+
+    assert AC3.reduce(csp, %{}, [:x, :y]) == {:no_solution, {:x, x_below_y}}
+
+    {:ok, reduced_csp, %{x: 1, y: 2}, []} = AC3.reduce(%{csp | domains: %{x: [1, 3], y: [1, 2]}}, %{}, [:x, :y])
+    assert reduced_csp.domains == %{x: [1], y: [2]}
+  end
+
+  test "support_counts/2 counts compatible neighbour values" do
+    assert AC3.support_counts(Problems.squares(3), :x) == %{0 => 1, 1 => 1, 2 => 0, 3 => 0}
+    assert AC3.support_counts(Problems.map_coloring(), :WA) == %{red: 4, green: 4, blue: 4}
+    assert AC3.support_counts(Problems.map_coloring(), :T) == %{red: 0, green: 0, blue: 0}
+  end
+
+  test "node_consistency/1 applies unary constraints" do
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: Enum.to_list(0..10), y: Enum.to_list(0..10)},
+      constraints: [{[:x], fn [x] -> x > 3 end}, {[:x, :y], fn [x, y] -> x < y end}]
+    }
+
+    assert {:ok, reduced_csp} = AC3.node_consistency(csp)
+    assert reduced_csp.domains == %{x: Enum.to_list(4..10), y: Enum.to_list(0..10)}
+
+    assert AC3.node_consistency(Csp.add_constraint(csp, {[:x], fn [x] -> x > 10 end})) == :no_solution
+  end
 end
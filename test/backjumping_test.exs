@@ -0,0 +1,41 @@
+defmodule Csp.BackjumpingTest do
+  use ExUnit.Case
+
+  alias Csp.{Backjumping, Backtracking}
+  alias Csp.Problems
+
+  test "finds the same first solution as backtracking" do
+    for csp <- [Problems.nqueens(8), Problems.map_coloring(), Problems.squares()] do
+      assert Backjumping.solve(csp) == Backtracking.solve(csp)
+    end
+  end
+
+  test "proves there is no solution" do
+    assert Backjumping.solve(Problems.nqueens(3)) == :no_solution
+    assert Csp.solve(Problems.nqueens(3), method: :backjumping) == :no_solution
+  end
+
+  test "visits fewer nodes than backtracking when the conflict is deep" do
+    # `z` always conflicts with `a = 1`, and `b1`, `b2`, `b3` in between are irrelevant to the conflict
+    variables = [:a, :b1, :b2, :b3, :z]
+
+    csp = %Csp{
+      variables: variables,
+      domains: Map.new(variables, &{&1, [1, 2]}),
+      constraints: [{[:a, :z], fn [a, z] -> a + z == 4 end}]
+    }
+
+    {result, stats} = Backtracking.solve_with_stats(csp)
+    {^result, backjumping_stats} = Backjumping.solve_with_stats(csp)
+
+    assert result == {:solved, %{a: 2, b1: 1, b2: 1, b3: 1, z: 2}}
+    assert stats.nodes == 37
+    assert backjumping_stats.nodes == 12
+    assert backjumping_stats.backjumps == 3
+  end
+
+  test "raises on unsupported options" do
+    assert_raise ArgumentError, fn -> Backjumping.solve(Problems.nqueens(4), all: true) end
+    assert_raise ArgumentError, fn -> Csp.solve(Problems.nqueens(4), method: :backjumping, forward_checking: true) end
+  end
+end
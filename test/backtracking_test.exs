@@ -0,0 +1,356 @@
+defmodule Csp.BacktrackingTest do
+  use ExUnit.Case
+
+  alias Csp.Backtracking
+  alias Csp.Problems
+
+  test "forward checking finds the same solutions as plain backtracking" do
+    csp = Problems.nqueens(6)
+
+    {:solved, solutions} = Backtracking.solve(csp, all: true)
+    {:solved, forward_checked_solutions} = Backtracking.solve(csp, all: true, forward_checking: true)
+
+    assert length(solutions) == 4
+    assert forward_checked_solutions == solutions
+  end
+
+  test "minimum remaining values selector breaks ties by variables order" do
+    csp = %Csp{
+      variables: [:a, :c, :b],
+      domains: %{a: [1, 2, 3], b: [1, 2], c: [1, 2]},
+      constraints: [{[:b, :c], fn [b, c] -> b != c end}]
+    }
+
+    assert {:solved, %{a: 1, b: 2, c: 1}} = Backtracking.solve(csp, variable_selector: :minimum_remaining_values)
+  end
+
+  test "degree heuristic breaking minimum remaining values ties visits fewer nodes in graph coloring" do
+    # `i1` and `i2` are isolated, and a 4-clique can't be colored with 3 colors
+    clique = [:a, :b, :c, :d]
+    edges = for x <- clique, y <- clique, x < y, do: {x, y}
+    csp = Problems.graph_coloring([:i1, :i2 | clique], edges, 3)
+
+    plain_minimum_remaining_values = fn unassigned, csp ->
+      variable = Enum.min_by(unassigned, &length(Map.fetch!(csp.domains, &1)))
+      {variable, List.delete(unassigned, variable)}
+    end
+
+    {:no_solution, plain_stats} =
+      Backtracking.solve_with_stats(csp, forward_checking: true, variable_selector: plain_minimum_remaining_values)
+
+    {:no_solution, stats} =
+      Backtracking.solve_with_stats(csp, forward_checking: true, variable_selector: :minimum_remaining_values)
+
+    # plain MRV colors the isolated vertices first, and proves the clique can't be colored for each of their colorings
+    assert stats.nodes * 9 < plain_stats.nodes
+  end
+
+  test "least constraining value selector tries values ruling out fewer neighbour values first" do
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [1, 2, 3], y: [1, 2, 3]},
+      constraints: [{[:x, :y], fn [x, y] -> x >= y end}]
+    }
+
+    assert {:solved, %{x: 1, y: 1}} = Backtracking.solve(csp)
+    assert {:solved, %{x: 3, y: 1}} = Backtracking.solve(csp, value_selector: :least_constraining_value)
+  end
+
+  test "custom value selector orders values of each variable" do
+    csp = Problems.nqueens(4)
+    reverse = fn _variable, domain -> Enum.reverse(domain) end
+
+    assert Backtracking.solve(csp) == {:solved, %{1 => 2, 2 => 4, 3 => 1, 4 => 3}}
+    assert Backtracking.solve(csp, value_selector: reverse) == {:solved, %{1 => 3, 2 => 1, 3 => 4, 4 => 2}}
+  end
+
+  test "prune option cuts off branches of the search" do
+    csp = %Csp{
+      variables: [:x, :y, :z],
+      domains: %{x: [1, 2, 3], y: [1, 2, 3], z: [1, 2, 3]},
+      constraints: [Csp.Constraints.all_different([:x, :y, :z])]
+    }
+
+    # a budget on the sum of `x` and `y`, checked once both are assigned
+    budget = fn assignment -> Map.get(assignment, :x, 0) + Map.get(assignment, :y, 0) > 3 end
+
+    assert Backtracking.solve(csp) == {:solved, %{x: 1, y: 2, z: 3}}
+    assert Backtracking.solve(csp, prune: fn assignment -> assignment[:y] == 2 end) == {:solved, %{x: 1, y: 3, z: 2}}
+    assert Backtracking.count(csp, prune: budget) == 2
+    assert Backtracking.solve(csp, prune: fn assignment -> Map.has_key?(assignment, :z) end) == :no_solution
+  end
+
+  test "stream lazily produces the same solutions as solve" do
+    csp = Problems.nqueens(6)
+
+    {:solved, solution} = Backtracking.solve(csp)
+    {:solved, solutions} = Backtracking.solve(csp, all: true)
+
+    assert Backtracking.stream(csp) |> Enum.take(1) == [solution]
+    assert Backtracking.stream(csp) |> Enum.to_list() == solutions
+  end
+
+  test "degree selector starts with the most constrained variable" do
+    csp = %Csp{
+      variables: [:a, :b, :c],
+      domains: %{a: [1, 2], b: [1, 2], c: [1, 2]},
+      constraints: [{[:b, :a], fn [b, a] -> b != a end}, {[:b, :c], fn [b, c] -> b != c end}]
+    }
+
+    assert {:solved, %{a: 1, b: 2, c: 1}} = Backtracking.solve(csp)
+    assert {:solved, %{a: 2, b: 1, c: 2}} = Backtracking.solve(csp, variable_selector: :degree)
+    assert {:solved, %{a: 2, b: 1, c: 2}} = Backtracking.solve(csp, variable_selector: :minimum_remaining_values)
+  end
+
+  test "count returns the number of solutions" do
+    assert Backtracking.count(Problems.nqueens(6)) == 4
+    assert Backtracking.count(Problems.nqueens(6), forward_checking: true) == 4
+    assert Backtracking.count(Problems.nqueens(3)) == 0
+  end
+
+  test "only constraints on the newly assigned variable are checked" do
+    check = fn _ ->
+      send(self(), :checked)
+      true
+    end
+
+    csp = %Csp{
+      variables: [:a, :b, :c],
+      domains: %{a: [1], b: [1], c: [1]},
+      constraints: [{[:a, :b], check}, {[:b, :c], check}]
+    }
+
+    assert {:solved, %{a: 1, b: 1, c: 1}} = Backtracking.solve(csp)
+    assert count_checks() == 2
+  end
+
+  defp count_checks() do
+    receive do
+      :checked -> 1 + count_checks()
+    after
+      0 -> 0
+    end
+  end
+
+  test "parallel search finds the same solutions as sequential search" do
+    csp = Problems.nqueens(6)
+
+    {:solved, solution} = Backtracking.solve(csp, parallel: true)
+    assert Csp.solved?(csp, solution)
+
+    assert Backtracking.solve(csp, parallel: true, all: true) == Backtracking.solve(csp, all: true)
+    assert Backtracking.solve(Problems.nqueens(3), parallel: true) == :no_solution
+  end
+
+  test "maintaining arc consistency doesn't change the CSP or the solutions" do
+    csp = Problems.nqueens(6)
+
+    {:solved, solutions} = Backtracking.solve(csp, all: true)
+
+    assert Backtracking.solve(csp, all: true, ac3: true) == {:solved, solutions}
+    assert Backtracking.solve(csp, all: true, ac3: true) == {:solved, solutions}
+    assert csp.domains == Problems.nqueens(6).domains
+  end
+
+  test "maintaining arc consistency never reassigns assigned variables" do
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [1, 2], y: [2]},
+      constraints: [{[:x, :y], fn [x, y] -> x == y end}]
+    }
+
+    assert Backtracking.solve(csp, all: true, ac3: true) == {:solved, %{x: 2, y: 2}}
+
+    {:solved, solutions} = Backtracking.solve(Problems.map_coloring(), all: true, ac3: true)
+    assert solutions == Enum.uniq(solutions)
+    assert {:solved, solutions} == Backtracking.solve(Problems.map_coloring(), all: true)
+  end
+
+  test "maintaining arc consistency visits fewer nodes than plain backtracking" do
+    csp = Problems.nqueens(8)
+
+    {result, stats} = Backtracking.solve_with_stats(csp)
+    {^result, ac3_stats} = Backtracking.solve_with_stats(csp, ac3: true)
+
+    assert ac3_stats.nodes < stats.nodes
+  end
+
+  test "solve_with_stats reports search statistics" do
+    csp = Problems.nqueens(8)
+
+    {result, stats} = Backtracking.solve_with_stats(csp)
+    assert result == Backtracking.solve(csp)
+    assert stats.max_depth == 8
+    assert stats.nodes > stats.backtracks
+    assert stats.constraint_checks > 0
+    assert is_integer(stats.elapsed)
+
+    {^result, forward_checking_stats} = Backtracking.solve_with_stats(csp, forward_checking: true)
+    assert forward_checking_stats.nodes < stats.nodes
+  end
+
+  test "cheap constraints are checked before expensive ones" do
+    table = Csp.Constraints.table([:x, :y], for(x <- 1..3, y <- 1..3, do: [x, y]))
+    less = {[:x, :y], fn [x, y] -> x < y end}
+    csp = %Csp{variables: [:x, :y], domains: %{x: [1, 2, 3], y: [1, 2, 3]}, constraints: [table, less]}
+
+    {{:solved, solutions}, stats} = Backtracking.solve_with_stats(csp, all: true)
+
+    assert length(solutions) == 3
+    # the table is only checked for the 3 assignments satisfying `less`
+    assert stats.checks_per_constraint == [3, 9]
+    assert stats.constraint_checks == 12
+  end
+
+  test "forward checking prunes all different constraints with matching" do
+    # 5 pigeons, 4 holes
+    pigeons = Enum.to_list(1..5)
+    domains = Map.new(pigeons, &{&1, [1, 2, 3, 4]})
+
+    global = %Csp{variables: pigeons, domains: domains, constraints: [Csp.Constraints.all_different(pigeons)]}
+    pairwise = %{global | constraints: Csp.Constraints.all_different_constraints(pigeons)}
+
+    {:no_solution, global_stats} = Backtracking.solve_with_stats(global, forward_checking: true)
+    {:no_solution, pairwise_stats} = Backtracking.solve_with_stats(pairwise, forward_checking: true)
+
+    assert global_stats.nodes == 4
+    assert global_stats.nodes < pairwise_stats.nodes
+  end
+
+  test "forward checking prunes cardinality constraints by counting" do
+    variables = [:a, :b, :c, :d]
+    domains = Map.new(variables, &{&1, [0, 1]})
+    csp = %Csp{variables: variables, domains: domains, constraints: [Csp.Constraints.at_least(variables, 1, 4)]}
+
+    {result, stats} = Backtracking.solve_with_stats(csp)
+    {^result, forward_checking_stats} = Backtracking.solve_with_stats(csp, forward_checking: true)
+
+    assert result == {:solved, %{a: 1, b: 1, c: 1, d: 1}}
+    # `a = 0` is rejected right away, and `a = 1` leaves only `1` in the other domains
+    assert forward_checking_stats.nodes == 5
+    assert forward_checking_stats.nodes < stats.nodes
+  end
+
+  test "forward checking rejects partial assignments violating linear constraints' bounds" do
+    csp = %Csp{
+      variables: [:x, :y, :z],
+      domains: Map.new([:x, :y, :z], &{&1, Enum.to_list(0..9)}),
+      constraints: [Csp.Constraints.linear([x: 1, y: 1, z: 1], :>=, 25)]
+    }
+
+    {result, stats} = Backtracking.solve_with_stats(csp)
+    {^result, forward_checking_stats} = Backtracking.solve_with_stats(csp, forward_checking: true)
+
+    assert result == {:solved, %{x: 7, y: 9, z: 9}}
+    # `x` from 0 to 6 is rejected right away, since even `y = z = 9` isn't enough
+    assert forward_checking_stats.nodes == 10
+    assert forward_checking_stats.nodes < stats.nodes
+  end
+
+  test "steps/2 exposes search events one at a time" do
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [1, 2], y: [1, 2]},
+      constraints: [{[:x, :y], fn [x, y] -> x < y end}]
+    }
+
+    assert Enum.to_list(Backtracking.steps(csp)) == [
+             {:assign, :x, 1},
+             {:assign, :y, 1},
+             {:reject, :y, 1},
+             {:assign, :y, 2},
+             {:solution, %{x: 1, y: 2}},
+             {:backtrack, :y},
+             {:assign, :x, 2},
+             {:assign, :y, 1},
+             {:reject, :y, 1},
+             {:assign, :y, 2},
+             {:reject, :y, 2},
+             {:backtrack, :y},
+             {:backtrack, :x}
+           ]
+
+    solutions = for {:solution, solution} <- Backtracking.steps(Problems.nqueens(6)), do: solution
+    assert {:solved, solutions} == Backtracking.solve(Problems.nqueens(6), all: true)
+  end
+
+  test "all solutions are returned in a stable, lexicographic order" do
+    solutions = [%{1 => 2, 2 => 4, 3 => 1, 4 => 3}, %{1 => 3, 2 => 1, 3 => 4, 4 => 2}]
+
+    assert Backtracking.solve(Problems.nqueens(4), all: true) == {:solved, solutions}
+    assert Enum.to_list(Backtracking.stream(Problems.nqueens(4))) == solutions
+
+    reversed = %{Problems.nqueens(4) | domains: Map.new(1..4, &{&1, [4, 3, 2, 1]})}
+    assert Backtracking.solve(reversed, all: true) == {:solved, Enum.reverse(solutions)}
+  end
+
+  test "solve_with_trace/2 records the search" do
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [1, 2], y: [1, 2]},
+      constraints: [{[:x, :y], fn [x, y] -> x < y end}]
+    }
+
+    assert Backtracking.solve_with_trace(csp) ==
+             {{:solved, %{x: 1, y: 2}},
+              [
+                %{event: :assign, variable: :x, value: 1, depth: 1},
+                %{event: :assign, variable: :y, value: 1, depth: 2},
+                %{event: :reject, variable: :y, value: 1, depth: 2},
+                %{event: :assign, variable: :y, value: 2, depth: 2},
+                %{event: :solution, assignment: %{x: 1, y: 2}, depth: 2}
+              ]}
+
+    {result, trace} = Backtracking.solve_with_trace(csp, all: true)
+    assert result == Backtracking.solve(csp, all: true)
+    assert length(trace) == 13
+    assert Enum.frequencies_by(trace, & &1.event) == %{assign: 6, reject: 3, solution: 1, backtrack: 3}
+    assert List.last(trace) == %{event: :backtrack, variable: :x, depth: 1}
+
+    assert {:no_solution, _trace} = Backtracking.solve_with_trace(Problems.nqueens(3))
+  end
+
+  test "solve_with_trace/2 records the variable and constraint of domain wipeouts" do
+    different = {[:x, :y], fn [x, y] -> x != y end}
+    csp = %Csp{variables: [:x, :y], domains: %{x: [1, 2], y: [1]}, constraints: [different]}
+
+    assert Backtracking.solve_with_trace(csp, forward_checking: true) ==
+             {{:solved, %{x: 2, y: 1}},
+              [
+                %{event: :assign, variable: :x, value: 1, depth: 1},
+                %{event: :wipeout, variable: :y, constraint: different, depth: 1},
+                %{event: :reject, variable: :x, value: 1, depth: 1},
+                %{event: :assign, variable: :x, value: 2, depth: 1},
+                %{event: :assign, variable: :y, value: 1, depth: 2},
+                %{event: :solution, assignment: %{x: 2, y: 1}, depth: 2}
+              ]}
+
+    # the assigned value is pinned, so AC-3 finds no support for it
+    {_result, trace} = Backtracking.solve_with_trace(csp, ac3: true)
+    assert %{event: :wipeout, variable: :x, constraint: ^different} = Enum.at(trace, 1)
+
+    # no pigeon's domain is empty after the first assignment, but the holes can't be all different
+    all_different = Csp.Constraints.all_different([:a, :b, :c])
+    pigeons = %Csp{variables: [:a, :b, :c], domains: Map.new([:a, :b, :c], &{&1, [1, 2]}), constraints: [all_different]}
+
+    {:no_solution, trace} = Backtracking.solve_with_trace(pigeons, forward_checking: true)
+    assert %{event: :wipeout, variable: :a, constraint: ^all_different} = Enum.at(trace, 1)
+  end
+
+  test "solve_with_trace/2 records backtracking from variables without values to try" do
+    csp = %Csp{variables: [:x, :y], domains: %{x: [1], y: []}, constraints: []}
+
+    assert Backtracking.solve_with_trace(csp) ==
+             {:no_solution,
+              [
+                %{event: :assign, variable: :x, value: 1, depth: 1},
+                %{event: :backtrack, variable: :y, depth: 2},
+                %{event: :backtrack, variable: :x, depth: 1}
+              ]}
+
+    csp = %{csp | domains: %{x: [1], y: [1]}}
+
+    assert Backtracking.solve_with_trace(csp, value_selector: fn _variable, _domain -> [] end) ==
+             {:no_solution, [%{event: :backtrack, variable: :x, depth: 1}]}
+  end
+end
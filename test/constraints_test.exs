@@ -1,7 +1,7 @@
 defmodule ConstraintsTest do
   use ExUnit.Case
 
-  alias Csp.Constraints
+  alias Csp.{Constraint, Constraints}
 
   test "Constraints.all_different_constraints/1 works" do
     constraints = Constraints.all_different_constraints([0, 1, 2])
@@ -10,4 +10,369 @@ defmodule ConstraintsTest do
     assert Constraints.all_different_constraints([]) == []
     assert Constraints.all_different_constraints([1]) == []
   end
+
+  test "Constraints.not_equal/2, Constraints.equal_to/2, and Constraints.all_equal_constraints/1 work" do
+    assert Constraint.satisfies?(Constraints.not_equal(:x, :y), %{x: 1, y: 2})
+    assert !Constraint.satisfies?(Constraints.not_equal(:x, :y), %{x: 1, y: 1})
+
+    assert Constraint.arguments(Constraints.equal_to(:x, 5)) == [:x]
+    assert Constraint.satisfies?(Constraints.equal_to(:x, 5), %{x: 5})
+    assert !Constraint.satisfies?(Constraints.equal_to(:x, 5), %{x: 4})
+
+    constraints = Constraints.all_equal_constraints([:x, :y, :z])
+    assert Enum.map(constraints, &Constraint.arguments/1) == [[:x, :y], [:y, :z]]
+    assert Enum.all?(constraints, &Constraint.satisfies?(&1, %{x: 1, y: 1, z: 1}))
+    assert !Enum.all?(constraints, &Constraint.satisfies?(&1, %{x: 1, y: 1, z: 2}))
+    assert Constraints.all_equal_constraints([:x]) == []
+  end
+
+  test "Constraints.all_different/1 works" do
+    constraint = Constraints.all_different([:x, :y, :z])
+
+    assert Constraint.arguments(constraint) == [:x, :y, :z]
+    assert Constraint.satisfies?(constraint, %{x: 1, y: 2, z: 3})
+    assert !Constraint.satisfies?(constraint, %{x: 1, y: 2, z: 1})
+    assert Constraint.satisfies?(constraint, %{x: 1, y: 2})
+
+    csp = %Csp{
+      variables: [:x, :y, :z],
+      domains: %{x: [1, 2, 3], y: [1, 2], z: [1]},
+      constraints: [constraint]
+    }
+
+    assert Csp.solve(csp) == {:solved, %{x: 3, y: 2, z: 1}}
+  end
+
+  test "Constraints.AllDifferent.prune/2 enforces generalized arc consistency" do
+    constraint = Constraints.all_different([:x, :y, :z])
+
+    assert Constraints.AllDifferent.prune(constraint, %{x: [1, 2], y: [1, 2], z: [1, 2, 3]}) ==
+             {:ok, %{x: [1, 2], y: [1, 2], z: [3]}}
+
+    assert Constraints.AllDifferent.prune(constraint, %{x: [1, 2], y: [2, 3], z: [1, 2, 3]}) ==
+             {:ok, %{x: [1, 2], y: [2, 3], z: [1, 2, 3]}}
+
+    assert Constraints.AllDifferent.prune(constraint, %{x: [2], y: [1, 2, 3], z: [1, 3]}) ==
+             {:ok, %{x: [2], y: [1, 3], z: [1, 3]}}
+
+    # pairwise inequalities don't detect this until two of the variables are assigned
+    assert Constraints.AllDifferent.prune(constraint, %{x: [1, 2], y: [1, 2], z: [1, 2]}) == :no_solution
+
+    # values left unmatched by a maximum matching support the values on alternating paths from them
+    assert Constraints.AllDifferent.prune(constraint, %{x: [1], y: [1, 2, 3], z: [1, 2, 3, 4]}) ==
+             {:ok, %{x: [1], y: [2, 3], z: [2, 3, 4]}}
+  end
+
+  test "Constraints.linear/3 works" do
+    constraint = Constraints.linear([x: 2, y: 3, z: -1], :<=, 7)
+
+    assert Constraint.arguments(constraint) == [:x, :y, :z]
+    assert Constraint.satisfies?(constraint, %{x: 1, y: 2, z: 1})
+    assert !Constraint.satisfies?(constraint, %{x: 1, y: 2, z: 0})
+    assert Constraint.satisfies?(constraint, %{x: 5})
+  end
+
+  test "Constraints.Linear.prune/2 removes values that can't satisfy the constraint" do
+    constraint = Constraints.linear([x: 2, y: 3, z: -1], :<=, 7)
+    domains = %{x: [0, 1, 2, 3], y: [0, 1, 2, 3], z: [0, 1]}
+
+    assert Constraints.Linear.prune(constraint, domains) == {:ok, %{domains | y: [0, 1, 2]}}
+    assert Constraints.Linear.prune(constraint, %{x: [1], y: [3], z: [0, 1]}) == :no_solution
+
+    not_equal = Constraints.linear([x: 1, y: 1], :!=, 3)
+    assert Constraints.Linear.prune(not_equal, %{x: [1], y: [1, 2]}) == {:ok, %{x: [1], y: [1]}}
+  end
+
+  test "Constraints.table/2 works" do
+    constraint = Constraints.table([:shirt, :pants], [[:white, :black], [:white, :blue], [:blue, :white]])
+
+    assert Constraint.satisfies?(constraint, %{shirt: :white, pants: :blue})
+    assert !Constraint.satisfies?(constraint, %{shirt: :blue, pants: :black})
+    assert Constraint.satisfies?(constraint, %{pants: :white})
+    assert !Constraint.satisfies?(constraint, %{shirt: :black})
+
+    csp = %Csp{
+      variables: [:shirt, :pants],
+      domains: %{shirt: [:blue, :white], pants: [:black, :blue]},
+      constraints: [constraint]
+    }
+
+    assert {:solved, [%{shirt: :white, pants: :black}, %{shirt: :white, pants: :blue}]} = Csp.solve(csp, all: true)
+  end
+
+  test "Constraints.lex_leq/2 works" do
+    constraint = Constraints.lex_leq([:a, :b], [:c, :d])
+
+    assert Constraint.satisfies?(constraint, %{a: 1, b: 3, c: 2, d: 0})
+    assert Constraint.satisfies?(constraint, %{a: 1, b: 2, c: 1, d: 2})
+    assert !Constraint.satisfies?(constraint, %{a: 1, b: 3, c: 1, d: 2})
+    assert Constraint.satisfies?(constraint, %{a: 1, c: 1, d: 0})
+    assert !Constraint.satisfies?(constraint, %{a: 2, c: 1})
+
+    # coloring of two adjacent nodes with three colors, with the symmetry between the nodes broken
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [1, 2, 3], y: [1, 2, 3]},
+      constraints: [Constraints.not_equal(:x, :y)]
+    }
+
+    assert Csp.Backtracking.count(csp) == 6
+    assert Csp.Backtracking.count(Csp.add_constraint(csp, Constraints.lex_leq([:x], [:y]))) == 3
+  end
+
+  test "Constraints.clause/1 works and allows solving SAT problems" do
+    constraint = Constraints.clause(x: true, y: false)
+
+    assert Constraint.arguments(constraint) == [:x, :y]
+    assert Constraint.satisfies?(constraint, %{x: true, y: true})
+    assert Constraint.satisfies?(constraint, %{x: false, y: false})
+    assert !Constraint.satisfies?(constraint, %{x: false, y: true})
+    assert Constraint.satisfies?(constraint, %{y: true})
+
+    # (a or b or not c) and (not a or c or d) and (not b or not c or not d) and (not a or not b or c)
+    # and (a or not d or not c) and (not a or not c)
+    clauses = [
+      [a: true, b: true, c: false],
+      [a: false, c: true, d: true],
+      [b: false, c: false, d: false],
+      [a: false, b: false, c: true],
+      [a: true, d: false, c: false],
+      [a: false, c: false]
+    ]
+
+    variables = [:a, :b, :c, :d]
+
+    csp = %Csp{
+      variables: variables,
+      domains: Map.new(variables, &{&1, [false, true]}),
+      constraints: Enum.map(clauses, &Constraints.clause/1)
+    }
+
+    {:solved, solutions} = Csp.solve(csp, all: true)
+    assert length(solutions) == 6
+
+    for solution <- solutions, literals <- clauses do
+      assert Enum.any?(literals, fn {variable, polarity} -> solution[variable] == polarity end)
+    end
+
+    unsatisfiable = %{csp | constraints: csp.constraints ++ [Constraints.clause(a: true), Constraints.clause(a: false)]}
+    assert Csp.solve(unsatisfiable) == :no_solution
+  end
+
+  test "Constraints.channeling/2 works" do
+    constraint = Constraints.channeling([:x0, :x1, :x2], [:y0, :y1, :y2])
+
+    assert Constraint.arguments(constraint) == [:x0, :x1, :x2, :y0, :y1, :y2]
+    assert Constraint.satisfies?(constraint, %{x0: 1, x1: 2, x2: 0, y0: 2, y1: 0, y2: 1})
+    assert !Constraint.satisfies?(constraint, %{x0: 1, y1: 2})
+    assert !Constraint.satisfies?(constraint, %{y0: 1, x1: 2})
+    assert !Constraint.satisfies?(constraint, %{x0: 3})
+    assert Constraint.satisfies?(constraint, %{x0: 1, x1: 2})
+
+    # workers `x` are assigned tasks, tasks `y` are assigned workers; worker 0 can't do task 0, task 2 needs worker 1
+    x = [:x0, :x1, :x2]
+    y = [:y0, :y1, :y2]
+
+    csp = %Csp{
+      variables: x ++ y,
+      domains: Map.new(x ++ y, &{&1, [0, 1, 2]}),
+      constraints: [
+        constraint,
+        Constraints.all_different(x),
+        {[:x0], fn [task] -> task != 0 end},
+        Constraints.equal_to(:y2, 1)
+      ]
+    }
+
+    assert Csp.Backtracking.count(csp) == 1
+    assert Csp.solve(csp) == {:solved, %{x0: 1, x1: 2, x2: 0, y0: 2, y1: 0, y2: 1}}
+  end
+
+  test "Constraints.negate/1 works" do
+    constraint = Constraints.negate({[:x, :y], fn [x, y] -> x == y end})
+
+    assert Constraint.arguments(constraint) == [:x, :y]
+    assert Constraint.satisfies?(constraint, %{x: 1, y: 2})
+    assert !Constraint.satisfies?(constraint, %{x: 1, y: 1})
+    assert Constraint.satisfies?(constraint, %{x: 1})
+
+    csp = %Csp{variables: [:x, :y], domains: %{x: [1, 2, 3], y: [1, 2, 3]}, constraints: [constraint]}
+
+    assert Csp.solve(csp, all: true) == Csp.solve(%{csp | constraints: [Constraints.not_equal(:x, :y)]}, all: true)
+  end
+
+  test "Constraints.conjunction/1 and Constraints.disjunction/1 work" do
+    conjunction = Constraints.conjunction([Constraints.equal_to(:x, 1), Constraints.not_equal(:x, :y)])
+
+    assert Constraint.arguments(conjunction) == [:x, :y]
+    assert Constraint.satisfies?(conjunction, %{x: 1, y: 2})
+    assert !Constraint.satisfies?(conjunction, %{x: 1, y: 1})
+    assert !Constraint.satisfies?(conjunction, %{x: 2})
+    assert Constraint.satisfies?(conjunction, %{x: 1})
+
+    # either x + y == 3, or x - y == 2
+    disjunction =
+      Constraints.disjunction([Constraints.linear([x: 1, y: 1], :==, 3), Constraints.linear([x: 1, y: -1], :==, 2)])
+
+    assert Constraint.arguments(disjunction) == [:x, :y]
+    assert Constraint.satisfies?(disjunction, %{x: 1, y: 2})
+    assert Constraint.satisfies?(disjunction, %{x: 3, y: 1})
+    assert !Constraint.satisfies?(disjunction, %{x: 1, y: 1})
+    assert Constraint.satisfies?(disjunction, %{x: 1})
+
+    csp = %Csp{variables: [:x, :y], domains: %{x: [0, 1, 2, 3], y: [0, 1, 2, 3]}, constraints: [disjunction]}
+
+    assert Csp.solve(csp, all: true) ==
+             {:solved, [%{x: 0, y: 3}, %{x: 1, y: 2}, %{x: 2, y: 0}, %{x: 2, y: 1}, %{x: 3, y: 0}, %{x: 3, y: 1}]}
+  end
+
+  test "Constraints.reified/2 works" do
+    constraint = Constraints.reified(:b, {[:x, :y], fn [x, y] -> x < y end})
+
+    assert Constraint.arguments(constraint) == [:b, :x, :y]
+    assert Constraint.satisfies?(constraint, %{b: 1, x: 1, y: 2})
+    assert Constraint.satisfies?(constraint, %{b: 0, x: 2, y: 1})
+    assert !Constraint.satisfies?(constraint, %{b: 0, x: 1, y: 2})
+    assert !Constraint.satisfies?(constraint, %{b: 1, x: 2, y: 1})
+    assert Constraint.satisfies?(constraint, %{x: 1, y: 2})
+    assert Constraint.satisfies?(constraint, %{b: 0, x: 1})
+
+    csp = %Csp{
+      variables: [:b, :x, :y],
+      domains: %{b: [0], x: [1, 2], y: [1, 2]},
+      constraints: [constraint]
+    }
+
+    # the indicator forces `x < y` to be false
+    assert Csp.solve(csp, all: true) == {:solved, [%{b: 0, x: 1, y: 1}, %{b: 0, x: 2, y: 1}, %{b: 0, x: 2, y: 2}]}
+  end
+
+  test "Constraints.element/3 works" do
+    constraint = Constraints.element(:i, [:a, :b, :c], :r)
+
+    assert Constraint.arguments(constraint) == [:i, :r, :a, :b, :c]
+    assert Constraint.satisfies?(constraint, %{i: 1, a: 5, b: 7, c: 9, r: 7})
+    assert !Constraint.satisfies?(constraint, %{i: 1, a: 5, b: 7, c: 9, r: 5})
+    assert !Constraint.satisfies?(constraint, %{i: 3, r: 5})
+    assert Constraint.satisfies?(constraint, %{i: 2, r: 5})
+    assert Constraint.satisfies?(constraint, %{a: 5, b: 7, r: 5})
+    assert !Constraint.satisfies?(constraint, %{a: 5, b: 7, c: 9, r: 6})
+
+    csp = %Csp{
+      variables: [:i, :a, :b, :c, :r],
+      domains: %{i: [2], a: [5], b: [7], c: [9], r: [5, 7, 9]},
+      constraints: [constraint]
+    }
+
+    assert Csp.solve(csp) == {:solved, %{i: 2, a: 5, b: 7, c: 9, r: 9}}
+  end
+
+  test "Constraints.cumulative/4 works" do
+    constraint = Constraints.cumulative([:a, :b], [2, 2], [1, 1], 1)
+
+    assert Constraint.arguments(constraint) == [:a, :b]
+    assert Constraint.satisfies?(constraint, %{a: 0, b: 2})
+    assert !Constraint.satisfies?(constraint, %{a: 0, b: 1})
+    assert !Constraint.satisfies?(constraint, %{a: 1, b: 0})
+    assert Constraint.satisfies?(constraint, %{a: 1})
+
+    csp = %Csp{variables: [:a, :b], domains: %{a: [0, 1, 2, 3], b: [0, 1, 2, 3]}, constraints: [constraint]}
+
+    assert {:solved, [%{a: 0, b: 2}, %{a: 0, b: 3}, %{a: 1, b: 3}, %{a: 2, b: 0}, %{a: 3, b: 0}, %{a: 3, b: 1}]} =
+             Csp.solve(csp, all: true)
+
+    assert Csp.Backtracking.count(%{csp | constraints: [Constraints.cumulative([:a, :b], [2, 2], [1, 1], 2)]}) == 16
+  end
+
+  test "Constraints.abs_diff/3 and Constraints.mod/3 work" do
+    abs_diff = Constraints.abs_diff(:x, :y, 2)
+
+    assert Constraint.arguments(abs_diff) == [:x, :y]
+    assert Constraint.satisfies?(abs_diff, %{x: 1, y: 3})
+    assert Constraint.satisfies?(abs_diff, %{x: 3, y: 1})
+    assert !Constraint.satisfies?(abs_diff, %{x: 1, y: 2})
+    assert Constraint.satisfies?(abs_diff, %{x: 1})
+
+    mod = Constraints.mod(:x, 3, 1)
+
+    assert Constraint.arguments(mod) == [:x]
+    assert Constraint.satisfies?(mod, %{x: 4})
+    assert Constraint.satisfies?(mod, %{x: -2})
+    assert !Constraint.satisfies?(mod, %{x: 3})
+    assert Constraint.satisfies?(mod, %{})
+
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: Enum.to_list(0..9), y: Enum.to_list(0..9)},
+      constraints: [abs_diff, mod, Constraints.mod(:y, 2, 0)]
+    }
+
+    assert Csp.solve(csp, all: true, forward_checking: true) == {:solved, [%{x: 4, y: 2}, %{x: 4, y: 6}]}
+
+    {:reduced, reduced_csp} = Csp.AC3.solve(csp)
+    assert reduced_csp.domains == %{x: [4], y: [2, 6]}
+  end
+
+  test "Constraints.at_most/3, Constraints.at_least/3, and Constraints.exactly/3 work" do
+    at_most = Constraints.at_most([:a, :b, :c], 1, 1)
+
+    assert Constraint.arguments(at_most) == [:a, :b, :c]
+    assert Constraint.satisfies?(at_most, %{a: 1, b: 0, c: 0})
+    assert !Constraint.satisfies?(at_most, %{a: 1, b: 1})
+
+    at_least = Constraints.at_least([:a, :b, :c], 1, 2)
+
+    assert Constraint.satisfies?(at_least, %{a: 1, b: 1, c: 0})
+    assert Constraint.satisfies?(at_least, %{a: 1})
+    assert !Constraint.satisfies?(at_least, %{a: 0, b: 0})
+
+    exactly = Constraints.exactly([:a, :b, :c], 1, 2)
+
+    assert Constraint.satisfies?(exactly, %{a: 1, b: 0, c: 1})
+    assert !Constraint.satisfies?(exactly, %{a: 1, b: 1, c: 1})
+    assert !Constraint.satisfies?(exactly, %{a: 0, b: 0})
+
+    csp = %Csp{variables: [:a, :b, :c], domains: %{a: [0, 1], b: [0, 1], c: [0, 1]}, constraints: [exactly]}
+
+    assert Csp.Backtracking.count(csp) == 3
+    # once `a` is 0, the budget is tight, and both `b` and `c` have to be 1
+    assert Csp.solve_from(csp, %{a: 0}) == {:solved, %{a: 0, b: 1, c: 1}}
+  end
+
+  test "Constraints.Cardinality.prune/2 narrows domains by counting" do
+    at_most = Constraints.at_most([:a, :b, :c], 1, 1)
+
+    assert Constraints.Cardinality.prune(at_most, %{a: [1], b: [0, 1], c: [1, 2]}) == {:ok, %{a: [1], b: [0], c: [2]}}
+    assert Constraints.Cardinality.prune(at_most, %{a: [1], b: [1], c: [0, 1]}) == :no_solution
+
+    at_least = Constraints.at_least([:a, :b, :c], 1, 2)
+
+    assert Constraints.Cardinality.prune(at_least, %{a: [0], b: [0, 1], c: [1, 2]}) == {:ok, %{a: [0], b: [1], c: [1]}}
+    assert Constraints.Cardinality.prune(at_least, %{a: [0], b: [0], c: [0, 1]}) == :no_solution
+
+    domains = %{a: [0, 1], b: [0, 1], c: [0, 1]}
+    assert Constraints.Cardinality.prune(Constraints.exactly([:a, :b, :c], 1, 2), domains) == {:ok, domains}
+  end
+
+  test "Constraints.n_values/3 works" do
+    at_most_two = Constraints.n_values([:a, :b, :c], :<=, 2)
+
+    assert Constraint.arguments(at_most_two) == [:a, :b, :c]
+    assert Constraint.satisfies?(at_most_two, %{a: 1, b: 2, c: 1})
+    assert !Constraint.satisfies?(at_most_two, %{a: 1, b: 2, c: 3})
+    assert Constraint.satisfies?(at_most_two, %{a: 1, b: 2})
+
+    exactly_three = Constraints.n_values([:a, :b, :c], :==, 3)
+
+    assert Constraint.satisfies?(exactly_three, %{a: 1, b: 2})
+    assert !Constraint.satisfies?(exactly_three, %{a: 1, b: 1})
+
+    # the map of Australia needs 3 colors
+    states = ~w(WA NT Q NSW V SA T)a
+    borders = [WA: :NT, WA: :SA, NT: :SA, NT: :Q, SA: :Q, SA: :NSW, SA: :V, Q: :NSW, NSW: :V]
+    coloring = Csp.Problems.graph_coloring(states, borders, 4)
+
+    assert {:solved, _coloring} = Csp.solve(Csp.add_constraint(coloring, Constraints.n_values(states, :<=, 3)))
+    assert Csp.solve(Csp.add_constraint(coloring, Constraints.n_values(states, :<=, 2))) == :no_solution
+  end
 end
@@ -31,4 +31,228 @@ defmodule CspTest do
 
     assert solutions = solutions_no_ac3
   end
+
+  test "CSP can be built incrementally" do
+    csp =
+      %Csp{}
+      |> Csp.add_variable(:x, [1, 2])
+      |> Csp.add_variable(:y, [1, 2])
+      |> Csp.add_constraint({[:x, :y], fn [x, y] -> x != y end})
+
+    assert {:ok, ^csp} = Csp.build(csp)
+    assert csp.variables == [:x, :y]
+
+    assert Csp.add_constraint(csp, {[:x, :z], fn [x, z] -> x != z end}) |> Csp.build() ==
+             {:error, {:unknown_variable, :z, 1}}
+
+    assert Csp.build(%{csp | variables: [:x, :y, :w]}) == {:error, {:missing_domain, :w}}
+  end
+
+  test "validate/1 reports the offending variable" do
+    assert Csp.validate(Problems.map_coloring()) == :ok
+
+    csp = %Csp{
+      variables: [:x],
+      domains: %{x: [1, 2]},
+      constraints: [{[:x], fn [x] -> x > 1 end}, {[:y, :x], fn [y, x] -> y > x end}]
+    }
+
+    assert Csp.validate(csp) == {:error, {:unknown_variable, :y, 1}}
+  end
+
+  test "solver gives up after timeout" do
+    # 10 pigeons cannot sit in 9 holes, but plain backtracking takes a long time to prove it
+    pigeons = Enum.to_list(1..10)
+
+    csp = %Csp{
+      variables: pigeons,
+      domains: Map.new(pigeons, fn pigeon -> {pigeon, Enum.to_list(1..9)} end),
+      constraints: Csp.Constraints.all_different_constraints(pigeons)
+    }
+
+    assert Csp.solve(csp, timeout: 100) == :timeout
+    assert Csp.solve(Problems.squares(), timeout: 5_000) == {:solved, %{x: 0, y: 0}}
+  end
+
+  test "CSPs and assignments are pretty-printed" do
+    assert to_string(Problems.squares(3)) <> "\n" ==
+             """
+             CSP with 2 variables and 1 constraints
+             Variables:
+               :x: 4 values
+               :y: 4 values
+             Constraints:
+               on :x, :y
+             """
+
+    assert Csp.format_assignment(%{y: 4, x: 2}) == ":x = 2\n:y = 4"
+  end
+
+  test "neighbours/2 returns variables sharing constraints" do
+    csp = Problems.map_coloring()
+
+    assert Csp.neighbours(csp, :WA) == MapSet.new([:SA, :NT])
+    assert Csp.neighbours(csp, :T) == MapSet.new()
+  end
+
+  test "variables of one CSP can have values of different types" do
+    # a shirt color and a size in the same problem, linked by what's in stock
+    csp = %Csp{
+      variables: [:color, :size, :in_stock],
+      domains: %{color: [:red, :blue], size: [38, 40, 42], in_stock: [true, false]},
+      constraints: [
+        {[:color, :size, :in_stock], fn [color, size, in_stock] -> in_stock == (color == :blue or size < 40) end},
+        {[:in_stock], fn [in_stock] -> in_stock end},
+        {[:size], fn [size] -> size > 38 end}
+      ]
+    }
+
+    assert Csp.solve(csp, all: true) ==
+             {:solved, [%{color: :blue, size: 40, in_stock: true}, %{color: :blue, size: 42, in_stock: true}]}
+  end
+
+  test "solver works with character and string domains" do
+    # a tiny crossword: a 3-letter across word and a 3-letter down word sharing the first letter
+    csp = %Csp{
+      variables: [:across, :down],
+      domains: %{across: ["cat", "dog", "owl"], down: ["ant", "oak", "elk"]},
+      constraints: [
+        {[:across, :down], fn [across, down] -> String.first(across) == String.first(down) end},
+        Csp.Constraints.all_different([:across, :down])
+      ]
+    }
+
+    assert Csp.solve(csp) == {:solved, %{across: "owl", down: "oak"}}
+    assert Csp.solve(csp, forward_checking: true, value_selector: :least_constraining_value) ==
+             {:solved, %{across: "owl", down: "oak"}}
+
+    letters = %Csp{
+      variables: [:a, :b],
+      domains: %{a: [?x, ?y], b: [?x, ?y]},
+      constraints: Csp.Constraints.all_different_constraints([:a, :b])
+    }
+
+    assert Csp.solve(letters) == {:solved, %{a: ?x, b: ?y}}
+  end
+
+  test "conflict_set/2 explains unsolvable CSPs" do
+    x_above_one = {[:x], fn [x] -> x > 1 end}
+    x_below_two = {[:x], fn [x] -> x < 2 end}
+    different = {[:x, :y], fn [x, y] -> x != y end}
+
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [1, 2], y: [1, 2]},
+      constraints: [different, x_above_one, x_below_two]
+    }
+
+    assert Csp.conflict_set(csp) == {:no_solution, [x_above_one, x_below_two]}
+    assert Csp.conflict_set(Problems.squares()) == Csp.solve(Problems.squares())
+  end
+
+  test "new/3 validates the CSP" do
+    constraints = [{[:x, :y], fn [x, y] -> x != y end}]
+
+    assert {:ok, %Csp{variables: [:x, :y]}} = Csp.new([:x, :y], %{x: [1, 2], y: [1, 2]}, constraints)
+    assert Csp.new([:x], %{x: [1, 2]}, constraints) == {:error, {:unknown_variable, :y, 0}}
+  end
+
+  test "solve_from/3 completes a partial assignment" do
+    csp = Problems.map_coloring()
+
+    {:solved, solution} = Csp.solve_from(csp, %{WA: :blue, T: :red})
+    assert %{WA: :blue, T: :red} = solution
+    assert Csp.solved?(csp, solution)
+
+    {:solved, solutions} = Csp.solve_from(csp, %{WA: :blue, NT: :red}, all: true)
+    assert Enum.all?(solutions, &match?(%{WA: :blue, NT: :red}, &1))
+
+    assert Csp.solve_from(csp, %{WA: :blue, NT: :blue}) == :no_solution
+    assert Csp.solve_from(csp, %{WA: :purple}) == :no_solution
+  end
+
+  test "solve_many/2 solves each CSP, reporting invalid ones separately" do
+    invalid = %Csp{variables: [:x], domains: %{x: []}, constraints: []}
+
+    assert Csp.solve_many([Problems.squares(), invalid, Problems.nqueens(3), Problems.map_coloring()]) == [
+             Csp.solve(Problems.squares()),
+             {:error, {:empty_domain, :x}},
+             :no_solution,
+             Csp.solve(Problems.map_coloring())
+           ]
+
+    assert Csp.solve_many([]) == []
+  end
+
+  test "problem_stats/1 describes the CSP structure" do
+    csp = %Csp{
+      variables: [:x, :y, :z, :w],
+      domains: %{x: [1, 2], y: [1, 2], z: [1, 2, 3], w: [1]},
+      constraints: [
+        {[:x, :y], fn [x, y] -> x != y end},
+        {[:y, :z], fn [y, z] -> y < z end},
+        {[:z], fn [z] -> z > 1 end}
+      ]
+    }
+
+    assert Csp.problem_stats(csp) == %{
+             variables: 4,
+             domain_sizes: 8,
+             constraints: 3,
+             arities: %{1 => 1, 2 => 2},
+             density: 2 / 6,
+             tightness: [0.5, 0.5]
+           }
+  end
+
+  test "empty domains are reported before solving" do
+    assert Csp.new([:x, :y], %{x: [], y: [1, 2]}, []) == {:error, {:empty_domain, :x}}
+  end
+
+  test "duplicate variables are reported" do
+    assert Csp.new([:x, :y, :x], %{x: [1, 2], y: [1, 2]}, []) == {:error, {:duplicate_variable, :x}}
+
+    csp = %Csp{} |> Csp.add_variable(:x, [1, 2]) |> Csp.add_variable(:x, [3])
+    assert Csp.build(csp) == {:error, {:duplicate_variable, :x}}
+  end
+
+  test "example Sudoku is solved" do
+    csp = Problems.wiki_sudoku()
+
+    {:solved, solution} = Csp.solve(csp, method: :ac3)
+
+    assert Csp.solved?(csp, solution)
+    assert Map.take(solution, Map.keys(Problems.wiki_sudoku_cells_map())) == Problems.wiki_sudoku_cells_map()
+    assert Enum.map(0..8, &solution[{0, &1}]) == [5, 3, 4, 6, 7, 8, 9, 1, 2]
+  end
+
+  test "all solving methods agree on random CSPs" do
+    method_opts = [
+      [method: :backtracking],
+      [method: :backtracking, forward_checking: true],
+      [method: :backtracking, ac3: true, variable_selector: :minimum_remaining_values],
+      [method: :backtracking, value_selector: :least_constraining_value, variable_selector: :degree],
+      [method: :backjumping],
+      [method: :ac3],
+      [method: :brute_force]
+    ]
+
+    for seed <- 1..20 do
+      csp = Problems.random_binary(6, 3, 0.6, 0.35, seed)
+
+      verdicts =
+        Enum.map(method_opts, fn opts ->
+          case Csp.solve(csp, opts) do
+            {:solved, solution} ->
+              assert Csp.solved?(csp, solution), "#{inspect(opts)} returned a wrong solution for seed #{seed}"
+              :solved
+
+            :no_solution ->
+              :no_solution
+          end
+        end)
+
+      assert length(Enum.uniq(verdicts)) == 1, "methods disagree for seed #{seed}: #{inspect(verdicts)}"
+    end
+  end
 end
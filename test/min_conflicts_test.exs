@@ -0,0 +1,25 @@
+defmodule Csp.MinConflictsTest do
+  use ExUnit.Case
+
+  alias Csp.MinConflicts
+  alias Csp.Problems
+
+  test "runs with the same seed produce the same results" do
+    csp = Problems.map_coloring()
+
+    result = MinConflicts.solve(csp, seed: 42, tabu_depth: 10)
+    assert {:solved, solution} = result
+    assert Csp.solved?(csp, solution)
+
+    assert MinConflicts.solve(csp, seed: 42, tabu_depth: 10) == result
+  end
+
+  test "doesn't change the random number generator of the calling process" do
+    :rand.seed(:exsss, {1, 2, 3})
+    rand_state = :rand.export_seed()
+
+    MinConflicts.solve(Problems.map_coloring(), seed: 42)
+    MinConflicts.solve(Problems.map_coloring())
+    assert :rand.export_seed() == rand_state
+  end
+end
@@ -0,0 +1,53 @@
+defmodule Csp.OptimizationTest do
+  use ExUnit.Case
+
+  alias Csp.Optimization
+
+  test "finds the best solution according to the objective" do
+    csp = %Csp{
+      variables: [:x, :y],
+      domains: %{x: [3, 2, 1, 0], y: [3, 2, 1, 0]},
+      constraints: [{[:x, :y], fn [x, y] -> x != y end}]
+    }
+
+    sum = fn assignment -> assignment |> Map.values() |> Enum.sum() end
+
+    assert {:solved, assignment, 1} = Optimization.solve(csp, sum, bound: sum)
+    assert Csp.solved?(csp, assignment)
+
+    assert {:solved, %{x: 3, y: 2}, 5} = Optimization.solve(csp, sum, goal: :maximize)
+  end
+
+  test "returns :no_solution for unsolvable CSPs" do
+    csp = %Csp{variables: [:x], domains: %{x: [1, 2]}, constraints: [{[:x], fn [x] -> x > 2 end}]}
+
+    assert Optimization.solve(csp, fn %{x: x} -> x end) == :no_solution
+  end
+
+  test "solve_max_csp/2 minimizes the weight of violated constraints" do
+    # x < y, y < z, and z < x can't hold together
+    csp = %Csp{
+      variables: [:x, :y, :z],
+      domains: %{x: [1, 2, 3], y: [1, 2, 3], z: [1, 2, 3]},
+      constraints: [
+        {[:x, :y], fn [x, y] -> x < y end},
+        {[:y, :z], fn [y, z] -> y < z end},
+        {[:z, :x], fn [z, x] -> z < x end}
+      ]
+    }
+
+    assert Csp.solve(csp) == :no_solution
+    assert {:solved, assignment, 1} = Optimization.solve_max_csp(csp)
+    assert Enum.count(csp.constraints, &(!Csp.Constraint.satisfies?(&1, assignment))) == 1
+
+    # dropping the cheapest soft constraint makes the rest satisfiable
+    assert Optimization.solve_max_csp(csp, weights: [:infinity, :infinity, 2]) == {:solved, %{x: 1, y: 2, z: 3}, 2}
+    assert {:solved, %{x: 2, y: 3, z: 1}, 3} = Optimization.solve_max_csp(csp, weights: [5, 3, 4])
+
+    unsolvable = %{csp | domains: %{x: [1], y: [1], z: [1]}}
+    assert Optimization.solve_max_csp(unsolvable, weights: [:infinity, 1, 1]) == :no_solution
+
+    assert_raise ArgumentError, fn -> Optimization.solve_max_csp(csp, weights: [1, 1]) end
+    assert_raise ArgumentError, fn -> Optimization.solve_max_csp(csp, weights: [1, 1, 1, 1]) end
+  end
+end
@@ -0,0 +1,67 @@
+defmodule Csp.ParserTest do
+  use ExUnit.Case
+
+  alias Csp.Parser
+
+  test "parses a problem definition" do
+    input = """
+    # two different digits
+    var x 0..9
+    var y 0..9
+
+    neq x y
+    eq x 5
+    """
+
+    {:ok, csp} = Parser.parse(input)
+
+    assert csp.variables == [:x, :y]
+    assert csp.domains.x == Enum.to_list(0..9)
+    assert length(csp.constraints) == 2
+
+    assert {:solved, %{x: 5, y: 0}} = Csp.solve(csp)
+  end
+
+  test "reports malformed lines" do
+    assert Parser.parse("var x 0..9\n\nfoo x") == {:error, "line 3: unexpected line: foo x"}
+    assert Parser.parse("var x 1,a") == {:error, "line 1: unexpected line: var x 1,a"}
+    assert Parser.parse("var x 0..9\nlinear x:2 =< 3") == {:error, "line 2: unexpected line: linear x:2 =< 3"}
+    assert {:error, "invalid CSP: " <> _} = Parser.parse("var x 0..9\nneq x y")
+  end
+
+  test "rejects descending ranges" do
+    assert Parser.parse("var x 0..9\nvar y 10..0") ==
+             {:error, "line 2: empty or descending range 10..0 for variable y"}
+
+    assert {:ok, csp} = Parser.parse("var x 3..3")
+    assert csp.domains.x == [3]
+  end
+
+  test "parses structural constraints" do
+    input = """
+    var x 0..9
+    var y 1,2,3
+    var z 0..9
+    alldiff x y z
+    linear x:1 y:1 z:1 == 6
+    linear x:1 z:-1 > 0
+    """
+
+    {:ok, csp} = Parser.parse(input)
+
+    assert csp.domains.y == [1, 2, 3]
+    assert {:solved, %{x: 2, y: 3, z: 1}} = Csp.solve(csp)
+  end
+
+  test "accepts a final solve line" do
+    assert {:ok, csp} = Parser.parse("var x 0..9\nvar y 0..9\nneq x y\nsolve\n")
+    assert csp.variables == [:x, :y]
+    assert length(csp.constraints) == 1
+  end
+
+  test "parses the example problem file used with the CLI" do
+    {:ok, csp} = Parser.parse(File.read!("examples/digits.csp"))
+
+    assert Csp.solve(csp, all: true) == {:solved, %{x: 8, y: 2}}
+  end
+end
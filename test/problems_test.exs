@@ -0,0 +1,48 @@
+defmodule Csp.ProblemsTest do
+  use ExUnit.Case
+
+  alias Csp.{Constraint, Problems}
+
+  test "random_binary/5 generates reproducible model B CSPs" do
+    csp = Problems.random_binary(10, 4, 0.5, 0.25, 7)
+
+    assert csp.variables == Enum.to_list(1..10)
+    assert Enum.all?(csp.domains, fn {_variable, domain} -> domain == [1, 2, 3, 4] end)
+    assert length(csp.constraints) == 23
+    assert Enum.all?(csp.constraints, &(length(Constraint.arguments(&1)) == 2))
+    assert Csp.validate(csp) == :ok
+
+    forbidden_pairs = fn constraint ->
+      [x, y] = Constraint.arguments(constraint)
+      for x_value <- 1..4, y_value <- 1..4, !Constraint.satisfies?(constraint, %{x => x_value, y => y_value}) do
+        {x_value, y_value}
+      end
+    end
+
+    assert Enum.all?(csp.constraints, &(length(forbidden_pairs.(&1)) == 4))
+
+    same_csp = Problems.random_binary(10, 4, 0.5, 0.25, 7)
+    assert Enum.map(csp.constraints, &Constraint.arguments/1) == Enum.map(same_csp.constraints, &Constraint.arguments/1)
+    assert Csp.solve(csp, all: true) == Csp.solve(same_csp, all: true)
+
+    :rand.seed(:exsss, {1, 2, 3})
+    rand_state = :rand.export_seed()
+    Problems.random_binary(10, 4, 0.5, 0.25, 7)
+    assert :rand.export_seed() == rand_state
+  end
+
+  test "graph_coloring/3 colors graphs with the given number of colors" do
+    states = ~w(WA NT Q NSW V SA T)a
+    borders = [WA: :NT, WA: :SA, NT: :SA, NT: :Q, SA: :Q, SA: :NSW, SA: :V, Q: :NSW, NSW: :V]
+
+    csp = Problems.graph_coloring(states, borders, 3)
+    assert Csp.validate(csp) == :ok
+
+    {:solved, coloring} = Csp.solve(csp)
+    assert Enum.all?(borders, fn {state1, state2} -> coloring[state1] != coloring[state2] end)
+    assert Enum.all?(Map.values(coloring), &(&1 in 1..3))
+
+    # WA, NT, and SA border each other, and an odd cycle like this triangle needs 3 colors
+    assert Csp.solve(Problems.graph_coloring(states, borders, 2)) == :no_solution
+  end
+end
@@ -0,0 +1,43 @@
+defmodule Csp.RestartsTest do
+  use ExUnit.Case
+
+  alias Csp.Restarts
+  alias Csp.Problems
+
+  test "luby/1 produces the Luby sequence" do
+    assert Enum.map(1..15, &Restarts.luby/1) == [1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]
+  end
+
+  test "solves CSPs with restarts" do
+    csp = Problems.nqueens(8)
+
+    assert {:solved, solution} = Restarts.solve(csp, seed: 42, luby_unit: 10)
+    assert Csp.solved?(csp, solution)
+    assert Restarts.solve(csp, seed: 42, luby_unit: 10) == {:solved, solution}
+
+    assert Restarts.solve(Problems.nqueens(3), luby_unit: 1_000) == :no_solution
+  end
+
+  test "tells giving up apart from proving there is no solution" do
+    assert Restarts.solve(Problems.nqueens(8), max_restarts: 1, luby_unit: 1) == :restarts_exhausted
+    assert Csp.solve(Problems.nqueens(8), method: :restarts, max_restarts: 1, luby_unit: 1) == :restarts_exhausted
+  end
+
+  test "restarts escape a hard part of the search space where a deterministic run stalls" do
+    # `a = 1` can't be extended to `z`, but it's only noticed after assigning all of `b1`, ..., `b10`
+    bs = Enum.map(1..10, &:"b#{&1}")
+    variables = [:a | bs] ++ [:z]
+
+    csp = %Csp{
+      variables: variables,
+      domains: Map.merge(Map.new(variables, &{&1, [1, 2]}), %{a: Enum.to_list(1..10)}),
+      constraints: [{[:a, :z], fn [a, _z] -> a != 1 end}]
+    }
+
+    {{:solved, _solution}, stats} = Csp.Backtracking.solve_with_stats(csp)
+
+    assert {:solved, solution} = Restarts.solve(csp, seed: 42, max_restarts: 10, luby_unit: 20)
+    assert Csp.solved?(csp, solution)
+    assert stats.nodes > Enum.sum(for run <- 1..10, do: Restarts.luby(run) * 20)
+  end
+end